@@ -0,0 +1,78 @@
+//! Benchmark modular exponentiation, comparing the Montgomery and naive paths, and
+//! multiplication, comparing the Karatsuba and schoolbook paths
+//!
+//! Run with `cargo bench`.
+
+#![deny(missing_docs)]
+
+use std::time::{Duration, Instant};
+
+use matasano_rust::math::BigUint;
+use matasano_rust::rng::{ChaCha20Rng, Rng};
+
+/// The number of exponentiations for each benchmark
+const MODPOW_ITERATIONS: u32 = 5;
+/// The number of multiplications for each benchmark
+const MUL_ITERATIONS: u32 = 200;
+
+/// Returns a deterministic pseudo-random BigUint with exactly bits significant bits.
+/// bits must be a multiple of 8.
+fn pseudo_random(bits: usize, seed: u64) -> BigUint {
+    let mut bytes = vec![0; bits / 8];
+    ChaCha20Rng::from_u64_seed(seed).fill_bytes(&mut bytes);
+
+    // Set the top bit, so the number has the requested size
+    bytes[0] |= 0x80;
+    BigUint::from_bytes_be(&bytes)
+}
+
+/// Returns the average time taken by f over iterations calls
+fn time<F: FnMut() -> BigUint>(iterations: u32, mut f: F) -> (Duration, BigUint) {
+    let start = Instant::now();
+    let mut result = BigUint::zero();
+    for _ in 0..iterations {
+        result = f();
+    }
+
+    (start.elapsed() / iterations, result)
+}
+
+/// Run the benchmarks
+fn main() {
+    for bits in [512, 1024, 2048] {
+        // An odd modulus, and a full-size exponent, like an RSA private key operation
+        let m = pseudo_random(bits, 1);
+        let m = if m.is_even() { m + BigUint::one() } else { m };
+        let base = pseudo_random(bits - 8, 2);
+        let e = pseudo_random(bits, 3);
+
+        let (naive, naive_result) = time(MODPOW_ITERATIONS, || base.modpow_naive(&e, &m));
+        let (montgomery, montgomery_result) = time(MODPOW_ITERATIONS, || base.modpow(&e, &m));
+        assert_eq!(naive_result, montgomery_result);
+
+        println!(
+            "modpow {:4} bits: naive {:10.3?}, montgomery {:10.3?}, speedup {:.1}x",
+            bits,
+            naive,
+            montgomery,
+            naive.as_secs_f64() / montgomery.as_secs_f64()
+        );
+    }
+
+    // Karatsuba should be slower below the threshold, and faster above it
+    for bits in [1024, 2048, 3072, 4096, 8192, 16384] {
+        let a = pseudo_random(bits, 4);
+        let b = pseudo_random(bits, 5);
+        let (schoolbook, schoolbook_result) = time(MUL_ITERATIONS, || a.mul_schoolbook(&b));
+        let (karatsuba, karatsuba_result) = time(MUL_ITERATIONS, || a.mul_karatsuba(&b));
+        assert_eq!(schoolbook_result, karatsuba_result);
+
+        println!(
+            "mul {:5} bits: schoolbook {:10.3?}, karatsuba {:10.3?}, speedup {:.2}x",
+            bits,
+            schoolbook,
+            karatsuba,
+            schoolbook.as_secs_f64() / karatsuba.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,499 @@
+//! The AES block cipher, from FIPS-197
+//!
+//! Aes uses S-box table lookups, so it is not constant-time. AesBitsliced computes the S-box
+//! using bitsliced arithmetic, so its timing does not depend on the key or data.
+
+#![deny(missing_docs)]
+
+use core::convert::TryInto;
+use core::fmt;
+
+use crate::cipher::BlockCipher;
+
+mod bitsliced;
+
+pub use bitsliced::AesBitsliced;
+
+/// The number of bytes in an AES block
+pub const AES_BLOCK_BYTES: usize = 16;
+/// The number of bytes in an AES-128 key
+pub const AES_128_KEY_BYTES: usize = 16;
+/// The number of bytes in an AES-192 key
+pub const AES_192_KEY_BYTES: usize = 24;
+/// The number of bytes in an AES-256 key
+pub const AES_256_KEY_BYTES: usize = 32;
+
+/// An AES block
+pub type AesBlock = [u8; AES_BLOCK_BYTES];
+
+/// The number of bytes in an AES word
+const WORD_BYTES: usize = 4;
+/// The number of rows in the AES state
+const ROWS: usize = 4;
+/// The number of columns in the AES state
+const COLUMNS: usize = AES_BLOCK_BYTES / ROWS;
+/// The number of rounds for AES-256, which has the most rounds
+const MAX_ROUNDS: usize = 14;
+
+/// The AES substitution box
+pub const SBOX: [u8; 256] = sbox();
+/// The inverse of the AES substitution box
+pub const INV_SBOX: [u8; 256] = inv_sbox();
+
+/// Returns the AES S-box: the multiplicative inverse in GF(2^8), followed by an affine
+/// transformation.
+const fn sbox() -> [u8; 256] {
+    let mut sbox = [0; 256];
+
+    // p and q are inverses, because 3 and 3 ^ -1 generate the multiplicative group
+    let mut p: u8 = 1;
+    let mut q: u8 = 1;
+    loop {
+        // Multiply p by 3
+        p = p ^ xtime(p);
+
+        // Divide q by 3
+        q ^= q << 1;
+        q ^= q << 2;
+        q ^= q << 4;
+        if q & 0x80 != 0 {
+            q ^= 0x09;
+        }
+
+        let affine = q ^ q.rotate_left(1) ^ q.rotate_left(2) ^ q.rotate_left(3) ^ q.rotate_left(4);
+        sbox[p as usize] = affine ^ 0x63;
+
+        if p == 1 {
+            break;
+        }
+    }
+
+    // Zero has no inverse, so it is handled as a special case
+    sbox[0] = 0x63;
+    sbox
+}
+
+/// Returns the inverse of the AES S-box.
+const fn inv_sbox() -> [u8; 256] {
+    let mut inv = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        inv[SBOX[i] as usize] = i as u8;
+        i += 1;
+    }
+
+    inv
+}
+
+/// Returns b multiplied by x (2) in GF(2^8), using the AES polynomial.
+/// Does not branch on b.
+const fn xtime(b: u8) -> u8 {
+    (b << 1) ^ ((b >> 7) * 0x1b)
+}
+
+/// Returns a multiplied by b in GF(2^8), using the AES polynomial.
+/// Branches on b, but not on a.
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+
+    product
+}
+
+/// The reasons AES setup can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AesError {
+    /// The key is not 16, 24, or 32 bytes long
+    InvalidKeyLength {
+        /// The length of the key
+        len: usize,
+    },
+}
+
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AesError::InvalidKeyLength { len } => write!(
+                f,
+                "AES keys must be {}, {}, or {} bytes long, but the key is {} bytes long",
+                AES_128_KEY_BYTES, AES_192_KEY_BYTES, AES_256_KEY_BYTES, len
+            ),
+        }
+    }
+}
+
+/// Returns the number of rounds for a key of key_len bytes.
+fn aes_rounds(key_len: usize) -> Result<usize, AesError> {
+    match key_len {
+        AES_128_KEY_BYTES => Ok(10),
+        AES_192_KEY_BYTES => Ok(12),
+        AES_256_KEY_BYTES => Ok(MAX_ROUNDS),
+        len => Err(AesError::InvalidKeyLength { len }),
+    }
+}
+
+/// Substitute each byte in word using the S-box table.
+fn sub_word(word: &mut [u8; WORD_BYTES]) {
+    for b in word.iter_mut() {
+        *b = SBOX[*b as usize];
+    }
+}
+
+/// Returns the expanded round keys for key, and the number of rounds.
+/// Uses sub_word to substitute the bytes in each key word.
+/// Round keys after the final round are zero.
+fn expand_key(
+    key: &[u8],
+    sub_word: fn(&mut [u8; WORD_BYTES]),
+) -> Result<([AesBlock; MAX_ROUNDS + 1], usize), AesError> {
+    let rounds = aes_rounds(key.len())?;
+    let key_words = key.len() / WORD_BYTES;
+    let total_words = COLUMNS * (rounds + 1);
+
+    let mut words = [[0u8; WORD_BYTES]; COLUMNS * (MAX_ROUNDS + 1)];
+    for (word, chunk) in words.iter_mut().zip(key.chunks_exact(WORD_BYTES)) {
+        word.copy_from_slice(chunk);
+    }
+
+    let mut rcon = 1;
+    for i in key_words..total_words {
+        let mut temp = words[i - 1];
+        if i % key_words == 0 {
+            temp.rotate_left(1);
+            sub_word(&mut temp);
+            temp[0] ^= rcon;
+            rcon = xtime(rcon);
+        } else if key_words > 6 && i % key_words == 4 {
+            sub_word(&mut temp);
+        }
+
+        for j in 0..WORD_BYTES {
+            words[i][j] = words[i - key_words][j] ^ temp[j];
+        }
+    }
+
+    let mut round_keys = [[0; AES_BLOCK_BYTES]; MAX_ROUNDS + 1];
+    for (round_key, round_words) in round_keys.iter_mut().zip(words.chunks_exact(COLUMNS)) {
+        for (dest, word) in round_key.chunks_exact_mut(WORD_BYTES).zip(round_words) {
+            dest.copy_from_slice(word);
+        }
+    }
+
+    Ok((round_keys, rounds))
+}
+
+/// XOR round_key into state.
+fn add_round_key(state: &mut AesBlock, round_key: &AesBlock) {
+    for (s, k) in state.iter_mut().zip(round_key) {
+        *s ^= k;
+    }
+}
+
+/// Substitute each byte in state using the S-box table.
+fn sub_bytes(state: &mut AesBlock) {
+    for s in state.iter_mut() {
+        *s = SBOX[*s as usize];
+    }
+}
+
+/// Substitute each byte in state using the inverse S-box table.
+fn inv_sub_bytes(state: &mut AesBlock) {
+    for s in state.iter_mut() {
+        *s = INV_SBOX[*s as usize];
+    }
+}
+
+/// Rotate row r of state left by r columns.
+/// The state is stored in column-major order.
+fn shift_rows(state: &mut AesBlock) {
+    let old = *state;
+    for r in 0..ROWS {
+        for c in 0..COLUMNS {
+            state[r + ROWS * c] = old[r + ROWS * ((c + r) % COLUMNS)];
+        }
+    }
+}
+
+/// Rotate row r of state right by r columns.
+fn inv_shift_rows(state: &mut AesBlock) {
+    let old = *state;
+    for r in 0..ROWS {
+        for c in 0..COLUMNS {
+            state[r + ROWS * ((c + r) % COLUMNS)] = old[r + ROWS * c];
+        }
+    }
+}
+
+/// Multiply each column of state by the AES MixColumns polynomial.
+fn mix_columns(state: &mut AesBlock) {
+    for column in state.chunks_exact_mut(ROWS) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        column[0] = a0 ^ all ^ xtime(a0 ^ a1);
+        column[1] = a1 ^ all ^ xtime(a1 ^ a2);
+        column[2] = a2 ^ all ^ xtime(a2 ^ a3);
+        column[3] = a3 ^ all ^ xtime(a3 ^ a0);
+    }
+}
+
+/// Multiply each column of state by the inverse of the AES MixColumns polynomial.
+fn inv_mix_columns(state: &mut AesBlock) {
+    for column in state.chunks_exact_mut(ROWS) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = gmul(a0, 14) ^ gmul(a1, 11) ^ gmul(a2, 13) ^ gmul(a3, 9);
+        column[1] = gmul(a0, 9) ^ gmul(a1, 14) ^ gmul(a2, 11) ^ gmul(a3, 13);
+        column[2] = gmul(a0, 13) ^ gmul(a1, 9) ^ gmul(a2, 14) ^ gmul(a3, 11);
+        column[3] = gmul(a0, 11) ^ gmul(a1, 13) ^ gmul(a2, 9) ^ gmul(a3, 14);
+    }
+}
+
+/// Encrypt block in place, using round_keys, and sub_bytes for SubBytes.
+/// The number of rounds is one less than the number of round keys.
+fn encrypt_with(block: &mut AesBlock, round_keys: &[AesBlock], sub_bytes: fn(&mut AesBlock)) {
+    let rounds = round_keys.len() - 1;
+
+    add_round_key(block, &round_keys[0]);
+
+    for round_key in &round_keys[1..rounds] {
+        sub_bytes(block);
+        shift_rows(block);
+        mix_columns(block);
+        add_round_key(block, round_key);
+    }
+
+    sub_bytes(block);
+    shift_rows(block);
+    add_round_key(block, &round_keys[rounds]);
+}
+
+/// Decrypt block in place, using round_keys, and inv_sub_bytes for InvSubBytes.
+/// The number of rounds is one less than the number of round keys.
+fn decrypt_with(block: &mut AesBlock, round_keys: &[AesBlock], inv_sub_bytes: fn(&mut AesBlock)) {
+    let rounds = round_keys.len() - 1;
+
+    add_round_key(block, &round_keys[rounds]);
+
+    for round_key in round_keys[1..rounds].iter().rev() {
+        inv_shift_rows(block);
+        inv_sub_bytes(block);
+        add_round_key(block, round_key);
+        inv_mix_columns(block);
+    }
+
+    inv_shift_rows(block);
+    inv_sub_bytes(block);
+    add_round_key(block, &round_keys[0]);
+}
+
+/// An AES-128, AES-192, or AES-256 cipher, with an expanded key.
+#[derive(Clone)]
+pub struct Aes {
+    /// The round keys, followed by unused zero keys
+    round_keys: [AesBlock; MAX_ROUNDS + 1],
+    /// The number of rounds, which depends on the key size
+    rounds: usize,
+}
+
+impl Aes {
+    /// Create an AES cipher using key.
+    ///
+    /// Returns an error if key is not 16, 24, or 32 bytes long.
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        let (round_keys, rounds) = expand_key(key, sub_word)?;
+        Ok(Aes { round_keys, rounds })
+    }
+
+    /// Returns the number of rounds: 10, 12, or 14.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Encrypt block in place.
+    pub fn encrypt_block(&self, block: &mut AesBlock) {
+        encrypt_with(block, &self.round_keys[..=self.rounds], sub_bytes);
+    }
+
+    /// Decrypt block in place.
+    pub fn decrypt_block(&self, block: &mut AesBlock) {
+        decrypt_with(block, &self.round_keys[..=self.rounds], inv_sub_bytes);
+    }
+}
+
+/// Returns block as an AES block.
+///
+/// Panics if block is not AES_BLOCK_BYTES long.
+fn aes_block(block: &mut [u8]) -> &mut AesBlock {
+    block
+        .try_into()
+        .expect("AES blocks must be AES_BLOCK_BYTES long")
+}
+
+impl BlockCipher for Aes {
+    fn block_size(&self) -> usize {
+        AES_BLOCK_BYTES
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        Aes::encrypt_block(self, aes_block(block))
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        Aes::decrypt_block(self, aes_block(block))
+    }
+}
+
+impl fmt::Debug for Aes {
+    /// Formats the cipher without its round keys.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Aes")
+            .field("rounds", &self.rounds)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use crate::hex::hex_decode;
+
+    /// The plaintext for the FIPS-197 Appendix C examples
+    const APPENDIX_C_PLAINTEXT: &str = "00112233445566778899aabbccddeeff";
+
+    /// Returns hex decoded into a block.
+    fn block(hex: &str) -> AesBlock {
+        hex_decode(hex).try_into().expect("hex is one block")
+    }
+
+    /// Check that key encrypts plaintext to ciphertext, and decrypts it back.
+    fn check_vector(key: &str, plaintext: &str, ciphertext: &str) {
+        let aes = Aes::new(&hex_decode(key)).expect("key is valid");
+
+        let mut b = block(plaintext);
+        aes.encrypt_block(&mut b);
+        assert_eq!(b, block(ciphertext), "key: {}", key);
+
+        aes.decrypt_block(&mut b);
+        assert_eq!(b, block(plaintext), "key: {}", key);
+
+        let aes = AesBitsliced::new(&hex_decode(key)).expect("key is valid");
+
+        aes.encrypt_block(&mut b);
+        assert_eq!(b, block(ciphertext), "bitsliced key: {}", key);
+
+        aes.decrypt_block(&mut b);
+        assert_eq!(b, block(plaintext), "bitsliced key: {}", key);
+    }
+
+    #[test]
+    fn sboxes() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x01], 0x7c);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(SBOX[0xff], 0x16);
+        assert_eq!(INV_SBOX[0x63], 0x00);
+        assert_eq!(INV_SBOX[0x00], 0x52);
+
+        for i in 0..=255 {
+            assert_eq!(INV_SBOX[SBOX[i] as usize] as usize, i);
+        }
+    }
+
+    #[test]
+    fn gf_multiply() {
+        // FIPS-197 section 4.2
+        assert_eq!(gmul(0x57, 0x83), 0xc1);
+        assert_eq!(gmul(0x57, 0x13), 0xfe);
+        assert_eq!(xtime(0x57), 0xae);
+        assert_eq!(xtime(0x8e), 0x07);
+    }
+
+    #[test]
+    fn key_expansion() {
+        // FIPS-197 Appendix A.1
+        let (round_keys, rounds) =
+            expand_key(&hex_decode("2b7e151628aed2a6abf7158809cf4f3c"), sub_word)
+                .expect("key is valid");
+        assert_eq!(rounds, 10);
+        assert_eq!(round_keys[1], block("a0fafe1788542cb123a339392a6c7605"));
+        assert_eq!(round_keys[10], block("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+        assert_eq!(round_keys[11], [0; AES_BLOCK_BYTES]);
+
+        // FIPS-197 Appendix A.3, the final words
+        let (round_keys, rounds) = expand_key(
+            &hex_decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"),
+            sub_word,
+        )
+        .expect("key is valid");
+        assert_eq!(rounds, 14);
+        assert_eq!(round_keys[14], block("fe4890d1e6188d0b046df344706c631e"));
+    }
+
+    #[test]
+    fn fips_197_appendix_b() {
+        check_vector(
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "3243f6a8885a308d313198a2e0370734",
+            "3925841d02dc09fbdc118597196a0b32",
+        );
+    }
+
+    #[test]
+    fn fips_197_appendix_c() {
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f",
+            APPENDIX_C_PLAINTEXT,
+            "69c4e0d86a7b0430d8cdb78070b4c55a",
+        );
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f1011121314151617",
+            APPENDIX_C_PLAINTEXT,
+            "dda97ca4864cdfe06eaf70a0ec0d7191",
+        );
+        check_vector(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            APPENDIX_C_PLAINTEXT,
+            "8ea2b7ca516745bfeafc49904b496089",
+        );
+    }
+
+    #[test]
+    fn rounds() {
+        assert_eq!(Aes::new(&[0; 16]).expect("key is valid").rounds(), 10);
+        assert_eq!(Aes::new(&[0; 24]).expect("key is valid").rounds(), 12);
+        assert_eq!(Aes::new(&[0; 32]).expect("key is valid").rounds(), 14);
+        assert_eq!(
+            format!("{:?}", Aes::new(&[0; 16]).expect("key is valid")),
+            "Aes { rounds: 10, .. }"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "AES blocks must be AES_BLOCK_BYTES long")]
+    fn invalid_block_length() {
+        let aes = Aes::new(&[0; 16]).expect("key is valid");
+        BlockCipher::encrypt_block(&aes, &mut [0; 15]);
+    }
+
+    #[test]
+    fn invalid_key_length() {
+        for len in [0, 1, 15, 17, 20, 31, 33, 64] {
+            assert_eq!(
+                Aes::new(&vec![0; len]).map(|aes| aes.rounds()),
+                Err(AesError::InvalidKeyLength { len })
+            );
+        }
+
+        assert_eq!(
+            format!("{}", AesError::InvalidKeyLength { len: 5 }),
+            "AES keys must be 16, 24, or 32 bytes long, but the key is 5 bytes long"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! A constant-time AES implementation, using a bitsliced S-box
+//!
+//! The S-box is computed as the GF(2^8) inverse x ^ 254, followed by the affine transformation.
+//! The state is stored as 8 bit planes, so each operation processes all 16 bytes at once, without
+//! any table lookups or data-dependent branches.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use crate::aes::{
+    aes_block, decrypt_with, encrypt_with, expand_key, AesBlock, AesError, AES_BLOCK_BYTES,
+    MAX_ROUNDS, WORD_BYTES,
+};
+use crate::cipher::BlockCipher;
+use crate::math::BYTE_BITS;
+
+/// Bit planes for up to 16 bytes: bit j of plane i is bit i of byte j
+type Planes = [u16; BYTE_BITS];
+
+/// The AES affine transformation constant, used in SubBytes
+const AFFINE_CONSTANT: u8 = 0x63;
+/// The inverse AES affine transformation constant, used in InvSubBytes
+const INV_AFFINE_CONSTANT: u8 = 0x05;
+
+/// Returns the bit planes of bytes.
+fn to_planes(bytes: &[u8]) -> Planes {
+    let mut planes = [0; BYTE_BITS];
+    for (j, &byte) in bytes.iter().enumerate() {
+        for (i, plane) in planes.iter_mut().enumerate() {
+            *plane |= (((byte >> i) & 1) as u16) << j;
+        }
+    }
+
+    planes
+}
+
+/// Fill bytes from planes.
+fn from_planes(planes: &Planes, bytes: &mut [u8]) {
+    for (j, byte) in bytes.iter_mut().enumerate() {
+        *byte = 0;
+        for (i, plane) in planes.iter().enumerate() {
+            *byte |= (((plane >> j) & 1) as u8) << i;
+        }
+    }
+}
+
+/// Returns a * b in GF(2^8), for each byte in the planes.
+fn mul(a: &Planes, b: &Planes) -> Planes {
+    let mut product = [0u16; 2 * BYTE_BITS - 1];
+    for (i, a_i) in a.iter().enumerate() {
+        for (j, b_j) in b.iter().enumerate() {
+            product[i + j] ^= a_i & b_j;
+        }
+    }
+
+    // Reduce using x ^ 8 = x ^ 4 + x ^ 3 + x + 1, from the highest power down
+    for k in (BYTE_BITS..product.len()).rev() {
+        product[k - 4] ^= product[k];
+        product[k - 5] ^= product[k];
+        product[k - 7] ^= product[k];
+        product[k - 8] ^= product[k];
+    }
+
+    let mut reduced = [0; BYTE_BITS];
+    reduced.copy_from_slice(&product[..BYTE_BITS]);
+    reduced
+}
+
+/// Returns x ^ (2 ^ n) in GF(2^8), for each byte in the planes.
+fn square_n(x: &Planes, n: usize) -> Planes {
+    let mut result = *x;
+    for _ in 0..n {
+        result = mul(&result, &result);
+    }
+
+    result
+}
+
+/// Returns x ^ 254 in GF(2^8), for each byte in the planes.
+/// This is the multiplicative inverse of x, and maps zero to zero.
+fn inverse(x: &Planes) -> Planes {
+    let x2 = square_n(x, 1);
+    let x3 = mul(&x2, x);
+    let x12 = square_n(&x3, 2);
+    let x14 = mul(&x12, &x2);
+    let x15 = mul(&x14, x);
+    let x240 = square_n(&x15, 4);
+    mul(&x240, &x14)
+}
+
+/// Returns a plane with every bit set, if bit i of constant is set, and zero otherwise.
+fn constant_plane(constant: u8, i: usize) -> u16 {
+    0u16.wrapping_sub(((constant >> i) & 1) as u16)
+}
+
+/// Returns the AES affine transformation of x.
+fn affine(x: &Planes) -> Planes {
+    let mut result = [0; BYTE_BITS];
+    for (i, r) in result.iter_mut().enumerate() {
+        *r = x[i]
+            ^ x[(i + 4) % BYTE_BITS]
+            ^ x[(i + 5) % BYTE_BITS]
+            ^ x[(i + 6) % BYTE_BITS]
+            ^ x[(i + 7) % BYTE_BITS]
+            ^ constant_plane(AFFINE_CONSTANT, i);
+    }
+
+    result
+}
+
+/// Returns the inverse AES affine transformation of x.
+fn inv_affine(x: &Planes) -> Planes {
+    let mut result = [0; BYTE_BITS];
+    for (i, r) in result.iter_mut().enumerate() {
+        *r = x[(i + 2) % BYTE_BITS]
+            ^ x[(i + 5) % BYTE_BITS]
+            ^ x[(i + 7) % BYTE_BITS]
+            ^ constant_plane(INV_AFFINE_CONSTANT, i);
+    }
+
+    result
+}
+
+/// Substitute each byte in bytes using the S-box, in constant time.
+fn sbox_bytes(bytes: &mut [u8]) {
+    let planes = affine(&inverse(&to_planes(bytes)));
+    from_planes(&planes, bytes);
+}
+
+/// Substitute each byte in bytes using the inverse S-box, in constant time.
+fn inv_sbox_bytes(bytes: &mut [u8]) {
+    let planes = inverse(&inv_affine(&to_planes(bytes)));
+    from_planes(&planes, bytes);
+}
+
+/// Substitute each byte in word using the S-box, in constant time.
+fn sub_word(word: &mut [u8; WORD_BYTES]) {
+    sbox_bytes(word);
+}
+
+/// Substitute each byte in state using the S-box, in constant time.
+fn sub_bytes(state: &mut AesBlock) {
+    sbox_bytes(state);
+}
+
+/// Substitute each byte in state using the inverse S-box, in constant time.
+fn inv_sub_bytes(state: &mut AesBlock) {
+    inv_sbox_bytes(state);
+}
+
+/// A constant-time AES-128, AES-192, or AES-256 cipher, with an expanded key.
+///
+/// Produces the same results as Aes, but is slower.
+#[derive(Clone)]
+pub struct AesBitsliced {
+    /// The round keys, followed by unused zero keys
+    round_keys: [AesBlock; MAX_ROUNDS + 1],
+    /// The number of rounds, which depends on the key size
+    rounds: usize,
+}
+
+impl AesBitsliced {
+    /// Create a constant-time AES cipher using key.
+    ///
+    /// Returns an error if key is not 16, 24, or 32 bytes long.
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        let (round_keys, rounds) = expand_key(key, sub_word)?;
+        Ok(AesBitsliced { round_keys, rounds })
+    }
+
+    /// Returns the number of rounds: 10, 12, or 14.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Encrypt block in place.
+    pub fn encrypt_block(&self, block: &mut AesBlock) {
+        encrypt_with(block, &self.round_keys[..=self.rounds], sub_bytes);
+    }
+
+    /// Decrypt block in place.
+    pub fn decrypt_block(&self, block: &mut AesBlock) {
+        decrypt_with(block, &self.round_keys[..=self.rounds], inv_sub_bytes);
+    }
+}
+
+impl BlockCipher for AesBitsliced {
+    fn block_size(&self) -> usize {
+        AES_BLOCK_BYTES
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        AesBitsliced::encrypt_block(self, aes_block(block))
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        AesBitsliced::decrypt_block(self, aes_block(block))
+    }
+}
+
+impl fmt::Debug for AesBitsliced {
+    /// Formats the cipher without its round keys.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AesBitsliced")
+            .field("rounds", &self.rounds)
+            .finish_non_exhaustive()
+    }
+}
+
+// Each plane holds one bit from every byte in a block
+const _: () = assert!(AES_BLOCK_BYTES <= u16::BITS as usize);
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    use crate::aes::{Aes, AES_256_KEY_BYTES, INV_SBOX, SBOX};
+    use crate::test_util::byte_generator;
+
+    #[test]
+    fn planes() {
+        let bytes: [u8; AES_BLOCK_BYTES] = core::array::from_fn(|i| (i * 17) as u8);
+        let mut round_trip = [0; AES_BLOCK_BYTES];
+        from_planes(&to_planes(&bytes), &mut round_trip);
+        assert_eq!(round_trip, bytes);
+
+        assert_eq!(to_planes(&[0x01, 0x80]), [1, 0, 0, 0, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn sboxes_match_tables() {
+        for chunk in (0..=255).collect::<Vec<u8>>().chunks(AES_BLOCK_BYTES) {
+            let mut forward = [0; AES_BLOCK_BYTES];
+            forward.copy_from_slice(chunk);
+            let mut backward = forward;
+
+            sbox_bytes(&mut forward);
+            inv_sbox_bytes(&mut backward);
+            for (j, &x) in chunk.iter().enumerate() {
+                assert_eq!(forward[j], SBOX[x as usize], "S-box of {:#04x}", x);
+                assert_eq!(
+                    backward[j], INV_SBOX[x as usize],
+                    "inverse S-box of {:#04x}",
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_table_implementation() {
+        let mut next = byte_generator(1);
+        for key_len in [16, 24, AES_256_KEY_BYTES] {
+            for _ in 0..20 {
+                let key: Vec<u8> = (0..key_len).map(|_| next()).collect();
+                let block: AesBlock = core::array::from_fn(|_| next());
+
+                let table = Aes::new(&key).expect("key is valid");
+                let bitsliced = AesBitsliced::new(&key).expect("key is valid");
+                assert_eq!(bitsliced.rounds(), table.rounds());
+
+                let mut expected = block;
+                table.encrypt_block(&mut expected);
+                let mut actual = block;
+                bitsliced.encrypt_block(&mut actual);
+                assert_eq!(actual, expected);
+
+                bitsliced.decrypt_block(&mut actual);
+                assert_eq!(actual, block);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_key_length() {
+        assert_eq!(
+            AesBitsliced::new(&[0; 17]).map(|aes| aes.rounds()),
+            Err(AesError::InvalidKeyLength { len: 17 })
+        );
+    }
+}
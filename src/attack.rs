@@ -0,0 +1,10 @@
+//! Attacks on ciphers
+//!
+//! The XOR attacks use the scoring module to recognise plaintexts, so they need the std feature.
+
+#![deny(missing_docs)]
+
+pub mod cbc;
+pub mod ecb;
+#[cfg(feature = "std")]
+pub mod xor;
@@ -0,0 +1,360 @@
+//! Attacks on CBC mode
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::{vec, vec::Vec};
+
+use crate::oracle::{EncryptionOracle, PaddingOracle};
+use crate::padding::pkcs7_pad;
+
+/// The byte used to fill chosen plaintext
+const FILL_BYTE: u8 = b'A';
+
+/// Returns data from an encryption oracle, modified so it decrypts to plaintext containing target.
+///
+/// The oracle must return an IV followed by the CBC ciphertext, and add a prefix of prefix_len
+/// bytes before the chosen plaintext. Fills the block after the prefix with FILL_BYTE, then flips
+/// bits in the previous ciphertext block or IV, so that block decrypts to target, followed by the
+/// rest of the chosen plaintext. The previous block decrypts to garbage, unless it is the IV.
+/// Makes one oracle query.
+///
+/// Panics if block_size is zero, or target is longer than block_size.
+pub fn cbc_bitflip<O: EncryptionOracle>(
+    mut oracle: O,
+    block_size: usize,
+    prefix_len: usize,
+    target: &[u8],
+) -> Vec<u8> {
+    assert!(block_size > 0, "Block size must not be zero");
+    assert!(
+        target.len() <= block_size,
+        "Bitflipping target must fit in one block"
+    );
+
+    let align_len = (block_size - prefix_len % block_size) % block_size;
+    let target_start = prefix_len + align_len;
+    let mut data = oracle.query(&vec![FILL_BYTE; align_len + block_size]);
+
+    // The IV shifts the ciphertext by one block, so the previous block starts at target_start
+    for (byte, &t) in data[target_start..].iter_mut().zip(target) {
+        *byte ^= FILL_BYTE ^ t;
+    }
+
+    data
+}
+
+/// The reasons a padding oracle attack can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PaddingOracleError {
+    /// The data is not an IV followed by one or more whole blocks
+    InvalidLength {
+        /// The length of the data
+        len: usize,
+        /// The block size
+        block_size: usize,
+    },
+    /// No guess gave valid padding for the byte at index in block
+    ByteNotFound {
+        /// The index of the ciphertext block, starting at zero
+        block: usize,
+        /// The index of the byte in the block
+        index: usize,
+    },
+}
+
+impl fmt::Display for PaddingOracleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaddingOracleError::InvalidLength { len, block_size } => write!(
+                f,
+                "Data length {} is not an IV and whole blocks of {} bytes",
+                len, block_size
+            ),
+            PaddingOracleError::ByteNotFound { block, index } => write!(
+                f,
+                "Padding oracle rejected every guess for block {} byte {}",
+                block, index
+            ),
+        }
+    }
+}
+
+/// Returns the block cipher decryption of block, using a padding oracle which takes an IV
+/// followed by the ciphertext. Errors report block_index as the block number.
+///
+/// Queries a chosen IV with the block, working backwards from the last byte. Each guess which
+/// gives valid padding reveals one more byte. If the last byte's guess is valid, the second last
+/// IV byte is changed, to rule out longer padding like 0x02 0x02. Makes up to 256 queries for
+/// each byte, and 128 on average.
+///
+/// Panics if block is empty, or longer than the PKCS#7 maximum block size.
+pub fn padding_oracle_intermediate<O: PaddingOracle>(
+    mut oracle: O,
+    block_index: usize,
+    block: &[u8],
+) -> Result<Vec<u8>, PaddingOracleError> {
+    let block_size = block.len();
+    assert!(
+        block_size > 0 && block_size <= u8::MAX as usize,
+        "Padding oracle block size must be between 1 and 255"
+    );
+
+    let mut intermediate = vec![0; block_size];
+    let mut query = vec![0; 2 * block_size];
+    query[block_size..].copy_from_slice(block);
+
+    for index in (0..block_size).rev() {
+        let pad = (block_size - index) as u8;
+        for (q, i) in query[index + 1..block_size]
+            .iter_mut()
+            .zip(&intermediate[index + 1..])
+        {
+            *q = i ^ pad;
+        }
+
+        let mut found = false;
+        for guess in 0..=u8::MAX {
+            query[index] = guess;
+            if !oracle.query(&query) {
+                continue;
+            }
+
+            // The previous plaintext byte might have matched a longer padding
+            if index == block_size - 1 && index > 0 {
+                query[index - 1] ^= 1;
+                let valid = oracle.query(&query);
+                query[index - 1] ^= 1;
+                if !valid {
+                    continue;
+                }
+            }
+
+            intermediate[index] = guess ^ pad;
+            found = true;
+            break;
+        }
+
+        if !found {
+            return Err(PaddingOracleError::ByteNotFound {
+                block: block_index,
+                index,
+            });
+        }
+    }
+
+    Ok(intermediate)
+}
+
+/// Returns the padded plaintext decrypted from data, an IV followed by CBC ciphertext, using a
+/// padding oracle which takes the same format.
+///
+/// Recovers the block cipher decryption of each block, then XORs it with the previous block, so
+/// the first block is decrypted using the IV. Does not remove the padding.
+///
+/// Returns an error if data is not an IV followed by whole blocks, or the oracle rejects every
+/// guess for a byte.
+/// Panics if block_size is zero, or larger than the PKCS#7 maximum block size.
+pub fn padding_oracle_decrypt<O: PaddingOracle>(
+    mut oracle: O,
+    block_size: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, PaddingOracleError> {
+    assert!(block_size > 0, "Block size must not be zero");
+    if data.len() < 2 * block_size || !data.len().is_multiple_of(block_size) {
+        return Err(PaddingOracleError::InvalidLength {
+            len: data.len(),
+            block_size,
+        });
+    }
+
+    let blocks: Vec<&[u8]> = data.chunks_exact(block_size).collect();
+    let mut plaintext = Vec::with_capacity(data.len() - block_size);
+    for (block_index, pair) in blocks.windows(2).enumerate() {
+        let intermediate = padding_oracle_intermediate(&mut oracle, block_index, pair[1])?;
+        plaintext.extend(intermediate.iter().zip(pair[0]).map(|(i, p)| i ^ p));
+    }
+
+    Ok(plaintext)
+}
+
+/// Returns an IV followed by CBC ciphertext, which decrypts to plaintext with PKCS#7 padding,
+/// using only a padding oracle (CBC-R).
+///
+/// Starts with an all-zero final ciphertext block, then works backwards: each previous block is
+/// the current block's decryption XOR its plaintext block. The last previous block is the IV.
+///
+/// Returns an error if the oracle rejects every guess for a byte.
+/// Panics if block_size is zero, or larger than the PKCS#7 maximum block size.
+pub fn padding_oracle_encrypt<O: PaddingOracle>(
+    mut oracle: O,
+    block_size: usize,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, PaddingOracleError> {
+    let padded = pkcs7_pad(plaintext, block_size);
+    let block_count = padded.len() / block_size;
+
+    let mut data = vec![0; padded.len() + block_size];
+    for block_index in (0..block_count).rev() {
+        let (previous, rest) = data.split_at_mut((block_index + 1) * block_size);
+        let intermediate =
+            padding_oracle_intermediate(&mut oracle, block_index, &rest[..block_size])?;
+
+        let plaintext_block = &padded[block_index * block_size..][..block_size];
+        for ((p, i), b) in previous[block_index * block_size..]
+            .iter_mut()
+            .zip(&intermediate)
+            .zip(plaintext_block)
+        {
+            *p = i ^ b;
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::{Aes, AES_BLOCK_BYTES};
+    use crate::cipher::{decrypt_padded, encrypt_padded, Mode};
+    use crate::oracle::comments::{CommentOracle, ADMIN_TOKEN, COMMENT_PREFIX};
+    use crate::oracle::padding::CbcPaddingOracle;
+    use crate::oracle::{Counting, FnOracle};
+    use crate::padding::{pkcs7_unpad, Padding};
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn bitflip_comment() {
+        let mut oracle = Counting::new(CommentOracle::new(ChaCha20Rng::from_u64_seed(16)));
+        let data = cbc_bitflip(
+            &mut oracle,
+            AES_BLOCK_BYTES,
+            COMMENT_PREFIX.len(),
+            ADMIN_TOKEN,
+        );
+        assert_eq!(oracle.queries(), 1);
+        assert_eq!(oracle.inner().is_admin(&data), Ok(true));
+
+        for target in [&b""[..], b"=", b";role=admin;x=;;", b"\x00\xff"] {
+            let data = cbc_bitflip(&mut oracle, AES_BLOCK_BYTES, COMMENT_PREFIX.len(), target);
+            let comment = oracle
+                .inner()
+                .decrypt_comment(&data)
+                .expect("comment is valid");
+            assert_eq!(&comment[COMMENT_PREFIX.len()..][..target.len()], target);
+        }
+    }
+
+    #[test]
+    fn bitflip_prefix() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        let iv = [3; AES_BLOCK_BYTES];
+        for prefix_len in [0, 1, 15, 16, 17, 40] {
+            let oracle = FnOracle(|chosen: &[u8]| {
+                let plaintext = [&vec![b'p'; prefix_len][..], chosen, b";suffix"].concat();
+                let ciphertext = encrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, &iv, &plaintext)
+                    .expect("IV is valid");
+                [&iv[..], &ciphertext].concat()
+            });
+
+            let data = cbc_bitflip(oracle, AES_BLOCK_BYTES, prefix_len, b"flipped");
+            let (flipped_iv, ciphertext) = data.split_at(AES_BLOCK_BYTES);
+            let plaintext = decrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, flipped_iv, ciphertext)
+                .expect("padding is valid");
+
+            let target_start = prefix_len.next_multiple_of(AES_BLOCK_BYTES);
+            assert_eq!(&plaintext[target_start..][..7], b"flipped");
+            assert!(plaintext.ends_with(b"AAAAAAAAA;suffix"));
+            if prefix_len == 0 {
+                // Flipping the IV does not garble any plaintext
+                assert!(plaintext.starts_with(b"flippedAAAAAAAAA"));
+            }
+        }
+    }
+
+    /// Plaintexts with lengths around block boundaries
+    const PLAINTEXTS: [&[u8]; 4] = [
+        b"",
+        b"YELLOW SUBMARINE",
+        b"000001With the bass kicked in and the Vega's are pumpin'",
+        b"\x02\x02\x01\x01\x01",
+    ];
+
+    #[test]
+    fn padding_oracle() {
+        let mut oracle = Counting::new(CbcPaddingOracle::new(ChaCha20Rng::from_u64_seed(17)));
+        for plaintext in PLAINTEXTS {
+            let data = oracle.inner_mut().encrypt(plaintext);
+            let padded = padding_oracle_decrypt(&mut oracle, AES_BLOCK_BYTES, &data)
+                .expect("attack succeeds");
+            assert_eq!(pkcs7_unpad(&padded, AES_BLOCK_BYTES), Ok(plaintext));
+        }
+
+        // Each byte takes up to 256 queries, and one more to check the last byte of each block
+        let max_queries = PLAINTEXTS
+            .iter()
+            .map(|p| (p.len() / AES_BLOCK_BYTES + 1) * (256 * AES_BLOCK_BYTES + AES_BLOCK_BYTES))
+            .sum();
+        assert!(oracle.queries() <= max_queries);
+    }
+
+    #[test]
+    fn padding_oracle_ambiguous() {
+        // A block cipher which does nothing, so the first guess gives 0x02 0x02 padding
+        let oracle = FnOracle(|data: &[u8]| {
+            let (iv, block) = data.split_at(data.len() / 2);
+            let plaintext: Vec<u8> = iv.iter().zip(block).map(|(i, b)| i ^ b).collect();
+            pkcs7_unpad(&plaintext, block.len()).is_ok()
+        });
+
+        let mut block = [7; AES_BLOCK_BYTES];
+        block[AES_BLOCK_BYTES - 2] = 0x02;
+        assert_eq!(
+            padding_oracle_intermediate(oracle, 0, &block),
+            Ok(block.to_vec())
+        );
+    }
+
+    #[test]
+    fn padding_oracle_cbc_r() {
+        let mut oracle = Counting::new(CbcPaddingOracle::new(ChaCha20Rng::from_u64_seed(18)));
+        for plaintext in PLAINTEXTS {
+            let data = padding_oracle_encrypt(&mut oracle, AES_BLOCK_BYTES, plaintext)
+                .expect("attack succeeds");
+            assert_eq!(oracle.inner().decrypt(&data), Ok(plaintext.to_vec()));
+        }
+    }
+
+    #[test]
+    fn padding_oracle_errors() {
+        let mut oracle = CbcPaddingOracle::new(ChaCha20Rng::from_u64_seed(17));
+        for len in [0, 16, 17, 40] {
+            assert_eq!(
+                padding_oracle_decrypt(&mut oracle, AES_BLOCK_BYTES, &vec![0; len]),
+                Err(PaddingOracleError::InvalidLength {
+                    len,
+                    block_size: AES_BLOCK_BYTES
+                })
+            );
+        }
+
+        let never = FnOracle(|_: &[u8]| false);
+        assert_eq!(
+            padding_oracle_decrypt(never, AES_BLOCK_BYTES, &[0; 48]),
+            Err(PaddingOracleError::ByteNotFound {
+                block: 0,
+                index: AES_BLOCK_BYTES - 1
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Bitflipping target must fit in one block")]
+    fn invalid_target_len() {
+        let oracle = FnOracle(|chosen: &[u8]| chosen.to_vec());
+        cbc_bitflip(oracle, AES_BLOCK_BYTES, 0, &[0; AES_BLOCK_BYTES + 1]);
+    }
+}
@@ -0,0 +1,526 @@
+//! Attacks on ECB mode
+
+#![deny(missing_docs)]
+
+use core::cmp::Reverse;
+use core::fmt;
+
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::cipher::{Mode, MAX_BLOCK_BYTES};
+use crate::cookie::{profile_for, KEY_VALUE_SEPARATOR, PAIR_SEPARATOR, PROFILE_ROLE};
+use crate::hex::hex_decode;
+use crate::oracle::{EncryptionOracle, Oracle};
+use crate::padding::pkcs7_pad;
+
+/// The number of identical chosen plaintext blocks that guarantee two aligned repeated blocks,
+/// whatever prefix the oracle adds.
+pub const DETECTION_BLOCKS: usize = 3;
+
+/// The repeated blocks in a ciphertext
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockRepetition {
+    /// The number of whole blocks in the ciphertext
+    pub block_count: usize,
+    /// The number of blocks which are identical to an earlier block
+    pub duplicate_count: usize,
+    /// The indexes of every block which is identical to another block, in ascending order
+    pub repeated_indexes: Vec<usize>,
+}
+
+impl BlockRepetition {
+    /// Returns true if any blocks repeat, which is likely for ECB, and unlikely for other modes.
+    pub fn has_repeats(&self) -> bool {
+        self.duplicate_count > 0
+    }
+}
+
+/// Returns the repeated blocks in ciphertext, split into block_size blocks.
+/// A trailing partial block is ignored.
+///
+/// Panics if block_size is zero.
+pub fn block_repetition(ciphertext: &[u8], block_size: usize) -> BlockRepetition {
+    assert!(block_size > 0, "Block size must not be zero");
+
+    let blocks: Vec<&[u8]> = ciphertext.chunks_exact(block_size).collect();
+
+    let mut duplicate_count = 0;
+    let mut repeated_indexes = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if blocks[..i].contains(block) {
+            duplicate_count += 1;
+        }
+        if blocks
+            .iter()
+            .enumerate()
+            .any(|(j, other)| i != j && block == other)
+        {
+            repeated_indexes.push(i);
+        }
+    }
+
+    BlockRepetition {
+        block_count: blocks.len(),
+        duplicate_count,
+        repeated_indexes,
+    }
+}
+
+/// Returns the top_n lines of text with the most repeated blocks, and their line indexes,
+/// starting at zero. Lines with equal repetition keep their original order.
+///
+/// Each line is hex. Blank lines are skipped, and surrounding whitespace is ignored.
+///
+/// Panics if any line is not valid hex, or block_size is zero.
+pub fn scan_ecb_hex_lines(
+    text: &str,
+    block_size: usize,
+    top_n: usize,
+) -> Vec<(usize, BlockRepetition)> {
+    let mut ranked: Vec<(usize, BlockRepetition)> = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| (index, block_repetition(&hex_decode(line), block_size)))
+        .collect();
+
+    ranked.sort_by_key(|(_, r)| Reverse(r.duplicate_count));
+    ranked.truncate(top_n);
+
+    ranked
+}
+
+/// Returns the most likely mode for ciphertext: ECB if any blocks repeat, and CBC otherwise.
+///
+/// Panics if block_size is zero.
+pub fn detect_ecb_or_cbc(ciphertext: &[u8], block_size: usize) -> Mode {
+    if block_repetition(ciphertext, block_size).has_repeats() {
+        Mode::Ecb
+    } else {
+        Mode::Cbc
+    }
+}
+
+/// Returns the most likely mode used by oracle: ECB or CBC.
+///
+/// Encrypts DETECTION_BLOCKS identical blocks of chosen plaintext. The oracle may add any prefix
+/// and suffix. Makes one oracle query.
+///
+/// Panics if block_size is zero.
+pub fn detect_oracle_ecb_or_cbc<O: EncryptionOracle>(mut oracle: O, block_size: usize) -> Mode {
+    let chosen = vec![0; DETECTION_BLOCKS * block_size];
+    detect_ecb_or_cbc(&oracle.query(&chosen), block_size)
+}
+
+/// The byte used to align chosen plaintext with block boundaries
+const ALIGN_BYTE: u8 = 0;
+/// Two different bytes used to fill chosen plaintext blocks, which are not ALIGN_BYTE
+const FILL_BYTES: [u8; 2] = [1, 2];
+
+/// The reasons a byte-at-a-time ECB decryption attack can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ByteAtATimeError {
+    /// The ciphertext length did not change in steps of up to MAX_BLOCK_BYTES
+    BlockSizeNotFound,
+    /// The oracle does not use ECB mode
+    NotEcb,
+    /// No aligned chosen plaintext blocks were found in the ciphertext
+    PrefixNotFound,
+    /// No candidate matched the secret byte at index
+    ByteNotFound {
+        /// The index of the byte in the secret
+        index: usize,
+    },
+}
+
+impl fmt::Display for ByteAtATimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ByteAtATimeError::BlockSizeNotFound => write!(
+                f,
+                "Oracle block size not found: must be at most {} bytes",
+                MAX_BLOCK_BYTES
+            ),
+            ByteAtATimeError::NotEcb => write!(f, "Oracle does not use ECB mode"),
+            ByteAtATimeError::PrefixNotFound => write!(f, "Oracle prefix length not found"),
+            ByteAtATimeError::ByteNotFound { index } => {
+                write!(f, "Secret byte {} not found", index)
+            }
+        }
+    }
+}
+
+/// The results of a byte-at-a-time ECB decryption attack
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SuffixRecovery {
+    /// The block size of the oracle's cipher
+    pub block_size: usize,
+    /// The length of the prefix the oracle adds before the chosen plaintext
+    pub prefix_len: usize,
+    /// The secret the oracle adds after the chosen plaintext
+    pub secret: Vec<u8>,
+}
+
+/// Returns the block size of oracle's padded block cipher, and the shortest chosen plaintext
+/// length which adds a block to the ciphertext.
+///
+/// Makes up to MAX_BLOCK_BYTES + 1 oracle queries.
+pub fn find_block_size<O: EncryptionOracle>(mut oracle: O) -> Option<(usize, usize)> {
+    let empty_len = oracle.query(&[]).len();
+    for chosen_len in 1..=MAX_BLOCK_BYTES {
+        let len = oracle.query(&vec![ALIGN_BYTE; chosen_len]).len();
+        if len > empty_len {
+            return Some((len - empty_len, chosen_len));
+        }
+    }
+
+    None
+}
+
+/// Returns the length of the prefix oracle adds before the chosen plaintext, if it uses ECB mode
+/// with block_size.
+///
+/// Fills two blocks with FILL_BYTES, after up to block_size ALIGN_BYTEs, until the filled blocks
+/// are aligned. Each fill byte is used, so prefix and secret bytes can not look like filled
+/// blocks. Makes up to 2 * block_size oracle queries.
+///
+/// Panics if block_size is zero.
+pub fn find_prefix_len<O: EncryptionOracle>(mut oracle: O, block_size: usize) -> Option<usize> {
+    assert!(block_size > 0, "Block size must not be zero");
+
+    for align_len in 0..block_size {
+        let [first, second] = FILL_BYTES.map(|fill| {
+            let mut chosen = vec![ALIGN_BYTE; align_len];
+            chosen.resize(align_len + 2 * block_size, fill);
+            oracle.query(&chosen)
+        });
+
+        let first: Vec<&[u8]> = first.chunks_exact(block_size).collect();
+        let second: Vec<&[u8]> = second.chunks_exact(block_size).collect();
+        let filled = (0..first.len().min(second.len()).saturating_sub(1)).find(|&i| {
+            first[i] == first[i + 1] && second[i] == second[i + 1] && first[i] != second[i]
+        });
+
+        if let Some(index) = filled {
+            return (index * block_size).checked_sub(align_len);
+        }
+    }
+
+    None
+}
+
+/// Returns the secret an ECB encryption oracle adds after chosen plaintext, by aligning each
+/// secret byte at the end of a block, then matching that block against every possible last byte.
+///
+/// The oracle may add a fixed prefix before the chosen plaintext, and must use PKCS#7 padding, or
+/// another padding which always adds at least one byte. Queries oracle to find the block size,
+/// confirm ECB mode, and find the prefix length. Then makes one block_size query for each
+/// alignment, and one dictionary query for each secret byte.
+///
+/// Returns an error if the oracle does not use ECB mode, or any step fails.
+pub fn byte_at_a_time_ecb<O: EncryptionOracle>(
+    mut oracle: O,
+) -> Result<SuffixRecovery, ByteAtATimeError> {
+    let (block_size, fill_len) =
+        find_block_size(&mut oracle).ok_or(ByteAtATimeError::BlockSizeNotFound)?;
+    if detect_oracle_ecb_or_cbc(&mut oracle, block_size) != Mode::Ecb {
+        return Err(ByteAtATimeError::NotEcb);
+    }
+    let prefix_len =
+        find_prefix_len(&mut oracle, block_size).ok_or(ByteAtATimeError::PrefixNotFound)?;
+
+    // The secret and padding fill the ciphertext when fill_len bytes are chosen
+    let empty_len = oracle.query(&[]).len();
+    let secret_len = (empty_len - fill_len)
+        .checked_sub(prefix_len)
+        .ok_or(ByteAtATimeError::PrefixNotFound)?;
+
+    // Complete the last prefix block, so chosen plaintext starts at first_block
+    let align_len = (block_size - prefix_len % block_size) % block_size;
+    let first_block = (prefix_len + align_len) / block_size;
+
+    // Ciphertexts with each secret byte position at the end of a block
+    let aligned: Vec<Vec<u8>> = (0..block_size)
+        .map(|position| oracle.query(&vec![ALIGN_BYTE; align_len + block_size - 1 - position]))
+        .collect();
+
+    // The chosen bytes are followed by the secret, so each block starts with known bytes
+    let mut known = vec![ALIGN_BYTE; block_size - 1];
+    for index in 0..secret_len {
+        let target_block = first_block + index / block_size;
+        let target = &aligned[index % block_size][target_block * block_size..][..block_size];
+
+        let window = &known[known.len() - (block_size - 1)..];
+        let mut dictionary = vec![ALIGN_BYTE; align_len];
+        for candidate in 0..=u8::MAX {
+            dictionary.extend_from_slice(window);
+            dictionary.push(candidate);
+        }
+        let dictionary = oracle.query(&dictionary);
+
+        let byte = (0..=u8::MAX)
+            .find(|&candidate| {
+                let block = first_block + candidate as usize;
+                &dictionary[block * block_size..][..block_size] == target
+            })
+            .ok_or(ByteAtATimeError::ByteNotFound { index })?;
+        known.push(byte);
+    }
+
+    Ok(SuffixRecovery {
+        block_size,
+        prefix_len,
+        secret: known.split_off(block_size - 1),
+    })
+}
+
+/// The character used to fill chosen email addresses
+const EMAIL_FILL: char = 'A';
+
+/// Returns a ciphertext for a profile with role, using an oracle which encrypts the profile for
+/// an email address, in ECB mode with block_size and PKCS#7 padding.
+///
+/// Uses the public profile format: the email is the first value, and the role is the last. Encrypts
+/// an email which puts role and its padding at the start of a block. Then encrypts an email which
+/// ends a block just before the role value, and replaces the following blocks. Makes two oracle
+/// queries.
+///
+/// Panics if block_size is zero or more than 127, or role or its padding contains '&' or '='.
+pub fn ecb_cut_and_paste<O>(mut oracle: O, block_size: usize, role: &str) -> Vec<u8>
+where
+    O: Oracle<Input = str, Output = Vec<u8>>,
+{
+    assert!(block_size > 0, "Block size must not be zero");
+    assert!(
+        block_size <= i8::MAX as usize,
+        "Block size must be at most 127, so padding is ASCII"
+    );
+    assert!(
+        !role.contains([PAIR_SEPARATOR, KEY_VALUE_SEPARATOR]),
+        "Role must not contain separators"
+    );
+
+    let template = profile_for("").encode();
+    let email_offset = template
+        .find(PAIR_SEPARATOR)
+        .expect("profile has several pairs");
+    let role_offset = template.len() - PROFILE_ROLE.len();
+
+    // Put the padded role at the start of a block
+    let align_len = (block_size - email_offset % block_size) % block_size;
+    let padded_role = pkcs7_pad(role.as_bytes(), block_size);
+    assert!(
+        !padded_role.contains(&(PAIR_SEPARATOR as u8))
+            && !padded_role.contains(&(KEY_VALUE_SEPARATOR as u8)),
+        "Padded role must not contain separators"
+    );
+    let mut email: String = core::iter::repeat_n(EMAIL_FILL, align_len).collect();
+    email.push_str(core::str::from_utf8(&padded_role).expect("padding is ASCII"));
+    let role_start = email_offset + align_len;
+    let role_blocks = &oracle.query(&email)[role_start..][..padded_role.len()];
+
+    // End a block just before the role value
+    let email_len = (block_size - role_offset % block_size) % block_size;
+    let email: String = core::iter::repeat_n(EMAIL_FILL, email_len).collect();
+    let mut forged = oracle.query(&email);
+    forged.truncate(role_offset + email_len);
+
+    forged.extend_from_slice(role_blocks);
+    forged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+
+    use crate::aes::Aes;
+    use crate::aes::AES_BLOCK_BYTES;
+    use crate::cipher::{encrypt, encrypt_padded};
+    use crate::hex::hex_encode;
+    use crate::oracle::ecb_suffix::EcbSuffixOracle;
+    use crate::oracle::profile::ProfileOracle;
+    use crate::oracle::{Counting, FnOracle};
+    use crate::padding::Padding;
+    use crate::rng::ChaCha20Rng;
+
+    const BLOCK: usize = 16;
+
+    #[test]
+    fn repetition() {
+        assert_eq!(block_repetition(&[], BLOCK), BlockRepetition::default());
+
+        let mut ciphertext = Vec::new();
+        for block in [1, 2, 1, 3, 1, 2] {
+            ciphertext.extend_from_slice(&[block; BLOCK]);
+        }
+        // Partial blocks are ignored
+        ciphertext.extend_from_slice(&[3; BLOCK - 1]);
+
+        let r = block_repetition(&ciphertext, BLOCK);
+        assert_eq!(r.block_count, 6);
+        assert_eq!(r.duplicate_count, 3);
+        assert_eq!(r.repeated_indexes, [0, 1, 2, 4, 5]);
+        assert!(r.has_repeats());
+
+        let r = block_repetition(&ciphertext, 4);
+        assert_eq!(r.block_count, 27);
+        assert_eq!(r.duplicate_count, 24);
+    }
+
+    #[test]
+    fn scan_lines() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        let plaintext = [b"repeated block!!".repeat(4), b"and a unique end".to_vec()].concat();
+
+        let mut text = String::new();
+        for index in 0..10u8 {
+            let (mode, iv) = if index == 6 {
+                (Mode::Ecb, Vec::new())
+            } else {
+                (Mode::Cbc, vec![index; BLOCK])
+            };
+            let ciphertext = encrypt(&aes, mode, &iv, &plaintext).expect("lengths are valid");
+            text.push_str(&hex_encode(&ciphertext));
+            text.push('\n');
+        }
+
+        let ranked = scan_ecb_hex_lines(&text, BLOCK, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, 6);
+        assert_eq!(ranked[0].1.duplicate_count, 3);
+        assert_eq!(ranked[0].1.repeated_indexes, [0, 1, 2, 3]);
+        assert!(!ranked[1].1.has_repeats());
+    }
+
+    #[test]
+    fn detect_oracle() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        for prefix_len in 0..BLOCK {
+            for mode in [Mode::Ecb, Mode::Cbc] {
+                let mut oracle = Counting::new(FnOracle(|chosen: &[u8]| {
+                    let plaintext = [&vec![b'p'; prefix_len][..], chosen, b"suffix"].concat();
+                    let mut padded = plaintext.clone();
+                    padded.resize(plaintext.len().next_multiple_of(BLOCK), 0);
+                    let iv = vec![7; mode.iv_len(BLOCK)];
+                    encrypt(&aes, mode, &iv, &padded).expect("lengths are valid")
+                }));
+                assert_eq!(detect_oracle_ecb_or_cbc(&mut oracle, BLOCK), mode);
+                assert_eq!(oracle.queries(), 1);
+            }
+        }
+    }
+
+    /// A secret which contains the chosen plaintext bytes, and spans several blocks
+    const SECRET: &[u8] = b"\x00\x01\x02 Rollin' in my 5.0\nWith my rag-top down\x02\x01\x00\x01";
+
+    #[test]
+    fn byte_at_a_time() {
+        let mut rng = ChaCha20Rng::from_u64_seed(12);
+        let mut oracle = Counting::new(EcbSuffixOracle::new(&mut rng, SECRET));
+        let recovery = byte_at_a_time_ecb(&mut oracle).expect("attack succeeds");
+        assert_eq!(
+            recovery,
+            SuffixRecovery {
+                block_size: AES_BLOCK_BYTES,
+                prefix_len: 0,
+                secret: SECRET.to_vec(),
+            }
+        );
+
+        // Each secret byte takes one dictionary query
+        assert!(oracle.queries() > SECRET.len());
+        assert!(oracle.queries() < SECRET.len() + 4 * AES_BLOCK_BYTES);
+
+        let mut oracle = EcbSuffixOracle::new(&mut rng, &[]);
+        let recovery = byte_at_a_time_ecb(&mut oracle).expect("attack succeeds");
+        assert_eq!(recovery.secret, []);
+    }
+
+    #[test]
+    fn byte_at_a_time_prefix() {
+        let mut rng = ChaCha20Rng::from_u64_seed(14);
+        for prefix_len in 0..3 * AES_BLOCK_BYTES {
+            // Prefixes which end with chosen plaintext bytes
+            for fill in [ALIGN_BYTE, FILL_BYTES[0], FILL_BYTES[1], b'p'] {
+                let prefix = vec![fill; prefix_len];
+                let oracle = EcbSuffixOracle::with_prefix(&mut rng, &prefix, SECRET);
+                assert_eq!(
+                    find_prefix_len(oracle.clone(), AES_BLOCK_BYTES),
+                    Some(prefix_len)
+                );
+                if prefix_len % 7 == 0 {
+                    let recovery = byte_at_a_time_ecb(oracle).expect("attack succeeds");
+                    assert_eq!(recovery.prefix_len, prefix_len);
+                    assert_eq!(recovery.secret, SECRET);
+                }
+            }
+        }
+
+        for _ in 0..10 {
+            let oracle = EcbSuffixOracle::with_random_prefix(&mut rng, SECRET);
+            let prefix_len = oracle.prefix_len();
+            let recovery = byte_at_a_time_ecb(oracle).expect("attack succeeds");
+            assert_eq!(recovery.prefix_len, prefix_len);
+            assert_eq!(recovery.secret, SECRET);
+        }
+    }
+
+    #[test]
+    fn byte_at_a_time_errors() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        let cbc = FnOracle(|chosen: &[u8]| {
+            let plaintext = [chosen, SECRET].concat();
+            encrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, &[7; BLOCK], &plaintext)
+                .expect("IV is valid")
+        });
+        assert_eq!(byte_at_a_time_ecb(cbc), Err(ByteAtATimeError::NotEcb));
+
+        let fixed_len = FnOracle(|_: &[u8]| vec![0; BLOCK]);
+        assert_eq!(
+            byte_at_a_time_ecb(fixed_len),
+            Err(ByteAtATimeError::BlockSizeNotFound)
+        );
+    }
+
+    #[test]
+    fn cut_and_paste() {
+        let mut rng = ChaCha20Rng::from_u64_seed(13);
+        for role in ["admin", "", "root", "administrator of everything"] {
+            let mut oracle = Counting::new(ProfileOracle::new(&mut rng));
+            let forged = ecb_cut_and_paste(&mut oracle, BLOCK, role);
+            assert_eq!(oracle.queries(), 2);
+
+            let profile = oracle
+                .inner()
+                .decrypt_profile(&forged)
+                .expect("profile is valid");
+            assert_eq!(profile.get("role"), Some(role));
+            assert_eq!(profile.get("uid"), Some("10"));
+            assert_eq!(profile.len(), 3);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Role must not contain separators")]
+    fn invalid_cut_and_paste_role() {
+        let mut rng = ChaCha20Rng::from_u64_seed(13);
+        ecb_cut_and_paste(ProfileOracle::new(&mut rng), BLOCK, "admin&uid=0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Padded role must not contain separators")]
+    fn invalid_cut_and_paste_padding() {
+        // 38 padding bytes are '&'
+        let mut rng = ChaCha20Rng::from_u64_seed(13);
+        ecb_cut_and_paste(ProfileOracle::new(&mut rng), 43, "admin");
+    }
+
+    #[test]
+    #[should_panic(expected = "Block size must not be zero")]
+    fn invalid_block_size_zero() {
+        block_repetition(&[1, 2, 3], 0);
+    }
+}
@@ -0,0 +1,378 @@
+//! Attacks on XOR ciphers
+
+#![deny(missing_docs)]
+
+use core::ops::RangeInclusive;
+
+use alloc::{vec, vec::Vec};
+
+use crate::hex::hex_decode;
+use crate::math::hamming_distance;
+use crate::score::{index_of_coincidence, rank_candidates, Candidate, Scorer};
+use crate::xor::{repeating_key_xor, single_byte_xor};
+
+/// The default range of repeating XOR key sizes
+pub const KEYSIZES: RangeInclusive<usize> = 2..=40;
+
+/// The number of most likely key sizes tried by break_repeating_key_xor()
+pub const KEYSIZE_CANDIDATES: usize = 3;
+
+/// A method for estimating the size of a repeating XOR key
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum KeysizeMethod {
+    /// The mean Hamming distance between adjacent blocks, normalised by the key size.
+    /// Lower distances are more likely.
+    #[default]
+    HammingDistance,
+    /// The mean index of coincidence of the transposed columns.
+    /// Higher indexes are more likely.
+    IndexOfCoincidence,
+}
+
+/// Returns the top_n most likely single-byte XOR keys for ciphertext, best first.
+///
+/// Tries every key, and scores each plaintext using scorer.
+pub fn break_single_byte_xor(
+    ciphertext: &[u8],
+    scorer: Scorer,
+    top_n: usize,
+) -> Vec<Candidate<u8>> {
+    let candidates = (0..=u8::MAX).map(|key| (key, single_byte_xor(ciphertext, key)));
+    rank_candidates(candidates, scorer, top_n)
+}
+
+/// Returns the top_n most likely single-byte XOR keys for a hex ciphertext, best first.
+///
+/// Panics if hex_ciphertext is not valid hex.
+pub fn break_single_byte_xor_hex(
+    hex_ciphertext: &str,
+    scorer: Scorer,
+    top_n: usize,
+) -> Vec<Candidate<u8>> {
+    break_single_byte_xor(&hex_decode(hex_ciphertext), scorer, top_n)
+}
+
+/// Returns the top_n lines of text which are most likely to be single-byte XOR encrypted, best
+/// first.
+///
+/// Each line is hex. Each candidate key is the line index, starting at zero, and the single-byte
+/// XOR key. Blank lines are skipped, and surrounding whitespace is ignored.
+///
+/// Panics if any line is not valid hex.
+pub fn scan_single_byte_xor_hex_lines(
+    text: &str,
+    scorer: Scorer,
+    top_n: usize,
+) -> Vec<Candidate<(usize, u8)>> {
+    let candidates = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .filter_map(|(index, line)| {
+            let best = break_single_byte_xor_hex(line, scorer, 1).pop()?;
+            Some(((index, best.key), best.plaintext))
+        });
+
+    rank_candidates(candidates, scorer, top_n)
+}
+
+/// Returns the mean Hamming distance between adjacent keysize blocks in ciphertext, divided by
+/// keysize. A trailing partial block is ignored.
+///
+/// Returns None if ciphertext has fewer than two complete blocks.
+/// Panics if keysize is zero.
+pub fn normalized_hamming_distance(ciphertext: &[u8], keysize: usize) -> Option<f64> {
+    assert!(keysize > 0, "Repeating XOR key size must not be zero");
+
+    let blocks: Vec<&[u8]> = ciphertext.chunks_exact(keysize).collect();
+    if blocks.len() < 2 {
+        return None;
+    }
+
+    let total: usize = blocks
+        .windows(2)
+        .map(|pair| hamming_distance(pair[0], pair[1]))
+        .sum();
+
+    Some(total as f64 / ((blocks.len() - 1) * keysize) as f64)
+}
+
+/// Returns the mean index of coincidence of each column of ciphertext, transposed using keysize.
+///
+/// Returns None if any column is shorter than two bytes.
+/// Panics if keysize is zero.
+pub fn transposed_index_of_coincidence(ciphertext: &[u8], keysize: usize) -> Option<f64> {
+    let columns = transpose(ciphertext, keysize);
+    if columns.iter().any(|column| column.len() < 2) {
+        return None;
+    }
+
+    let total: f64 = columns
+        .iter()
+        .map(|column| index_of_coincidence(column))
+        .sum();
+    Some(total / keysize as f64)
+}
+
+/// Returns the key sizes in keysizes, most likely first, estimated using method.
+///
+/// Key sizes which are too long to estimate for ciphertext are skipped.
+pub fn rank_keysizes(
+    ciphertext: &[u8],
+    keysizes: RangeInclusive<usize>,
+    method: KeysizeMethod,
+) -> Vec<usize> {
+    // Higher scores are more likely
+    let mut scored: Vec<(usize, f64)> = keysizes
+        .filter_map(|keysize| {
+            let score = match method {
+                KeysizeMethod::HammingDistance => {
+                    -normalized_hamming_distance(ciphertext, keysize)?
+                }
+                KeysizeMethod::IndexOfCoincidence => {
+                    transposed_index_of_coincidence(ciphertext, keysize)?
+                }
+            };
+            Some((keysize, score))
+        })
+        .collect();
+
+    scored.sort_by(|(_, left), (_, right)| right.total_cmp(left));
+    scored.into_iter().map(|(keysize, _)| keysize).collect()
+}
+
+/// Returns keysize columns, where column i contains every byte in ciphertext at an index equal
+/// to i mod keysize.
+///
+/// Panics if keysize is zero.
+pub fn transpose(ciphertext: &[u8], keysize: usize) -> Vec<Vec<u8>> {
+    assert!(keysize > 0, "Repeating XOR key size must not be zero");
+
+    let mut columns = vec![Vec::new(); keysize];
+    for (i, &byte) in ciphertext.iter().enumerate() {
+        columns[i % keysize].push(byte);
+    }
+
+    columns
+}
+
+/// Returns the shortest prefix of key, which repeats to make key.
+fn shortest_period(key: &[u8]) -> &[u8] {
+    let period = (1..key.len())
+        .filter(|&period| key.len().is_multiple_of(period))
+        .find(|&period| key.iter().zip(&key[period..]).all(|(a, b)| a == b))
+        .unwrap_or(key.len());
+
+    &key[..period]
+}
+
+/// Returns the top_n most likely repeating XOR keys for ciphertext, best first.
+///
+/// Estimates the key size using method, then breaks each column as single-byte XOR. Tries the
+/// KEYSIZE_CANDIDATES most likely key sizes in KEYSIZES. Scores each column and plaintext using
+/// scorer, so character frequency scorers work best.
+pub fn break_repeating_key_xor(
+    ciphertext: &[u8],
+    method: KeysizeMethod,
+    scorer: Scorer,
+    top_n: usize,
+) -> Vec<Candidate<Vec<u8>>> {
+    let mut keys: Vec<Vec<u8>> = Vec::new();
+    for keysize in rank_keysizes(ciphertext, KEYSIZES, method)
+        .into_iter()
+        .take(KEYSIZE_CANDIDATES)
+    {
+        let key: Vec<u8> = transpose(ciphertext, keysize)
+            .iter()
+            .map(|column| break_single_byte_xor(column, scorer, 1)[0].key)
+            .collect();
+
+        // Multiples of the key size produce repeated keys
+        let key = shortest_period(&key).to_vec();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let candidates = keys.into_iter().map(|key| {
+        let plaintext = repeating_key_xor(ciphertext, &key);
+        (key, plaintext)
+    });
+
+    rank_candidates(candidates, scorer, top_n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+
+    use crate::hex::hex_encode;
+    use crate::test_util::byte_generator;
+
+    const PLAINTEXT: &[u8] = b"Cooking MC's like a pound of bacon";
+
+    /// English prose, long enough to break keys of up to 29 bytes
+    const LONG_PLAINTEXT: &[u8] = b"The lighthouse keeper climbed the narrow stairs every \
+evening, carrying a lamp and a small notebook. In the notebook he wrote down the weather, the \
+ships that passed, and anything else that seemed worth remembering. Most nights were quiet, \
+and the entries were short: a few words about the wind, the colour of the sea, and the time \
+the light was lit. But on some nights the storms came in from the west, and the waves broke \
+over the rocks below the tower. On those nights he stayed awake until morning, watching the \
+water and listening for the sound of engines. Over the years he saw fishing boats, cargo \
+ships, and once a tall sailing ship that looked as if it had come from another century. He \
+never learned its name. When he retired, he gave the notebooks to the village library, where \
+they sit on a shelf near the window. Children sometimes read them on rainy afternoons, and \
+they ask the librarian whether the stories are true. She tells them that the keeper wrote \
+down only what he saw, and that the sea is stranger than anything people could invent. Then \
+she shows them the old photograph of the tower, taken on the day the light was first lit, \
+with the keeper standing at the door in his heavy coat. The tower is still there, although \
+the light is now automatic, and nobody climbs the stairs in the evening any more.";
+
+    /// The repeating XOR key for 1.6
+    const LONG_KEY: &[u8] = b"Terminator X: Bring the noise";
+
+    #[test]
+    fn single_byte() {
+        let ciphertext = single_byte_xor(PLAINTEXT, b'X');
+        let ranked = break_single_byte_xor(&ciphertext, Scorer::default(), 5);
+
+        assert_eq!(ranked.len(), 5);
+        assert_eq!(ranked[0].key, b'X');
+        assert_eq!(ranked[0].plaintext, PLAINTEXT);
+
+        // Every key is a candidate
+        assert_eq!(
+            break_single_byte_xor(&ciphertext, Scorer::Ngram, 1000).len(),
+            256
+        );
+        assert!(break_single_byte_xor(&ciphertext, Scorer::Ngram, 0).is_empty());
+    }
+
+    #[test]
+    fn single_byte_hex() {
+        let hex = hex_encode(&single_byte_xor(PLAINTEXT, 0x35));
+        for scorer in [Scorer::ChiSquared, Scorer::LogLikelihood, Scorer::Ngram] {
+            let best = &break_single_byte_xor_hex(&hex, scorer, 1)[0];
+            assert_eq!(best.key, 0x35, "{:?}", scorer);
+            assert_eq!(best.plaintext, PLAINTEXT);
+        }
+    }
+
+    #[test]
+    fn scan_lines() {
+        // Pseudo-random lines, with one encrypted line
+        let mut next = byte_generator(1);
+        let mut text = String::new();
+        for index in 0..50 {
+            let line = if index == 17 {
+                single_byte_xor(b"Now that the party is jumping\n", 0x35)
+            } else {
+                (0..30).map(|_| next()).collect()
+            };
+            text.push_str(&hex_encode(&line));
+            text.push('\n');
+            if index == 3 {
+                // Blank lines are skipped
+                text.push_str("  \n");
+            }
+        }
+
+        let ranked = scan_single_byte_xor_hex_lines(&text, Scorer::default(), 3);
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(ranked[0].key, (18, 0x35));
+        assert_eq!(ranked[0].plaintext, b"Now that the party is jumping\n");
+
+        assert!(scan_single_byte_xor_hex_lines("\n\n", Scorer::default(), 3).is_empty());
+    }
+
+    #[test]
+    fn keysize_statistics() {
+        assert_eq!(normalized_hamming_distance(b"abc", 2), None);
+        assert_eq!(normalized_hamming_distance(b"abab", 2), Some(0.0));
+        assert_eq!(normalized_hamming_distance(&[0, 0xff, 0], 1), Some(8.0));
+
+        assert_eq!(transposed_index_of_coincidence(b"abc", 2), None);
+        assert_eq!(transposed_index_of_coincidence(b"abab", 2), Some(1.0));
+        assert_eq!(transposed_index_of_coincidence(b"abba", 2), Some(0.0));
+    }
+
+    #[test]
+    fn transpose_columns() {
+        assert_eq!(transpose(b"", 2), [b"", b""]);
+        assert_eq!(transpose(b"abcde", 2), [&b"ace"[..], &b"bd"[..]]);
+        assert_eq!(transpose(b"abc", 5), [&b"a"[..], b"b", b"c", b"", b""]);
+    }
+
+    #[test]
+    fn periods() {
+        assert_eq!(shortest_period(b""), b"");
+        assert_eq!(shortest_period(b"a"), b"a");
+        assert_eq!(shortest_period(b"aaaa"), b"a");
+        assert_eq!(shortest_period(b"ICEICE"), b"ICE");
+        assert_eq!(shortest_period(b"ICEIC"), b"ICEIC");
+        assert_eq!(shortest_period(b"abab_abab_"), b"abab_");
+    }
+
+    #[test]
+    fn keysizes() {
+        for method in [
+            KeysizeMethod::HammingDistance,
+            KeysizeMethod::IndexOfCoincidence,
+        ] {
+            for key in [&b"ICE"[..], b"YELLOW SUBMARINE", LONG_KEY] {
+                let ciphertext = repeating_key_xor(LONG_PLAINTEXT, key);
+                let ranked = rank_keysizes(&ciphertext, KEYSIZES, method);
+                let best = &ranked[..KEYSIZE_CANDIDATES];
+                assert!(
+                    best.iter().any(|keysize| keysize % key.len() == 0),
+                    "{:?} ranked {:?} for {} byte key",
+                    method,
+                    best,
+                    key.len()
+                );
+            }
+        }
+
+        // Key sizes which are too long are skipped
+        assert_eq!(
+            rank_keysizes(&[0; 9], KEYSIZES, KeysizeMethod::default()),
+            [2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn repeating_key() {
+        for method in [
+            KeysizeMethod::HammingDistance,
+            KeysizeMethod::IndexOfCoincidence,
+        ] {
+            for key in [&b"ICE"[..], b"YELLOW SUBMARINE", LONG_KEY] {
+                let ciphertext = repeating_key_xor(LONG_PLAINTEXT, key);
+                let ranked = break_repeating_key_xor(&ciphertext, method, Scorer::default(), 2);
+                assert!(!ranked.is_empty());
+                assert_eq!(ranked[0].key, key, "{:?}", method);
+                assert_eq!(ranked[0].plaintext, LONG_PLAINTEXT);
+            }
+        }
+
+        assert!(
+            break_repeating_key_xor(b"a", KeysizeMethod::default(), Scorer::default(), 2)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "key size must not be zero")]
+    fn invalid_transpose_keysize_zero() {
+        transpose(b"abc", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid hex")]
+    fn invalid_hex_line() {
+        scan_single_byte_xor_hex_lines("00\nzz\n", Scorer::default(), 1);
+    }
+}
@@ -2,12 +2,14 @@
 
 #![deny(missing_docs)]
 
-// I don't want to create a duplicate module here. But I can't get "crate" to work.
-#[path = "math.rs"]
-#[allow(dead_code)]
-mod math;
+use core::fmt;
+use core::str;
 
-use math::BYTE_BITS;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::display;
+use crate::math::{self, BYTE_BITS};
 
 /// The number of bits in a Base64 digit
 const B64_CHAR_BITS: usize = 6;
@@ -21,6 +23,8 @@ const B64_BLOCK_BYTES: usize = 3;
 const B64_BLOCK_CHARS: usize = 4;
 /// The number of bits in a Base64 conversion block
 const B64_BLOCK_BITS: usize = B64_BLOCK_BYTES * BYTE_BITS;
+// The length calculations rely on each block being a whole number of bytes and characters
+const _: () = assert!(B64_BLOCK_BITS == B64_BLOCK_CHARS * B64_CHAR_BITS);
 
 /// The maximum number of Base64 padding characters at the end of the final block
 const MAX_B64_PAD_CHARS: usize = B64_BLOCK_BYTES - 1;
@@ -42,74 +46,150 @@ fn base64_encode_char(char_bits: u8) -> char {
     }
 }
 
-/// Encode the B64_BLOCK_BYTES bytes in block, into a B64_BLOCK_CHARS character Base64 string.
+/// Encode the B64_BLOCK_BYTES bytes in block, into B64_BLOCK_CHARS Base64 characters.
 ///
 /// If the block is less than B64_BLOCK_BYTES long, the caller must zero-fill it.
-/// pad_count is the number of bytes that were zero-filled. The returned string is padded with that
-/// many B64_PAD_C padding characters.
+/// pad_count is the number of bytes that were zero-filled. The returned characters are padded with
+/// that many B64_PAD_C padding characters.
 ///
 /// Panics if:
 ///  * block is not B64_BLOCK_BYTES long. or
 ///  * pad_count is greater than MAX_B64_PAD_CHARS
-fn base64_encode_block(block: &[u8], pad_count: usize) -> String {
+fn base64_encode_block(block: &[u8], pad_count: usize) -> [char; B64_BLOCK_CHARS] {
     assert!(block.len() == B64_BLOCK_BYTES);
     assert!(pad_count <= MAX_B64_PAD_CHARS);
 
-    let mut s = String::with_capacity(B64_BLOCK_CHARS);
-    let mut c = Vec::<u8>::with_capacity(B64_BLOCK_CHARS);
+    let c = [
+        (block[0] & 0b11111100) >> 2,
+        (block[0] & 0b00000011) << 4 | (block[1] & 0b11110000) >> 4,
+        (block[1] & 0b00001111) << 2 | (block[2] & 0b11000000) >> 6,
+        block[2] & 0b00111111,
+    ];
 
-    c.push((block[0] & 0b11111100) >> 2);
-    c.push((block[0] & 0b00000011) << 4 | (block[1] & 0b11110000) >> 4);
-    c.push((block[1] & 0b00001111) << 2 | (block[2] & 0b11000000) >> 6);
-    c.push(block[2] & 0b00111111);
+    let mut s = [B64_PAD_C; B64_BLOCK_CHARS];
 
-    s.push(base64_encode_char(c[0]));
-    s.push(base64_encode_char(c[1]));
+    s[0] = base64_encode_char(c[0]);
+    s[1] = base64_encode_char(c[1]);
     // Special handling for padding
     if pad_count == 2 {
         assert!(c[2] == 0);
-        s.push(B64_PAD_C);
     } else {
-        s.push(base64_encode_char(c[2]));
+        s[2] = base64_encode_char(c[2]);
     }
     if pad_count >= 1 {
         assert!(c[3] == 0);
-        s.push(B64_PAD_C);
     } else {
-        s.push(base64_encode_char(c[3]));
+        s[3] = base64_encode_char(c[3]);
     }
 
-    assert!(s.len() == B64_BLOCK_CHARS);
     s
 }
 
-/// Encode bytes into a Base64 string.
+/// Encode a (possibly partial) final block of bytes into B64_BLOCK_CHARS Base64 characters,
+/// zero-filling and padding it if needed.
 ///
-/// If bytes is not a multiple of B64_BLOCK_BYTES long, the returned string is padded with a
-/// B64_PAD_C padding character for each missing byte.
-pub fn base64_encode(bytes: &[u8]) -> String {
+/// Panics if block is empty, or longer than B64_BLOCK_BYTES.
+fn base64_encode_partial_block(block: &[u8]) -> [char; B64_BLOCK_CHARS] {
+    assert!(!block.is_empty());
+    assert!(block.len() <= B64_BLOCK_BYTES);
+
+    let pad_count: usize = B64_BLOCK_BYTES - block.len();
+    let mut filled = [0; B64_BLOCK_BYTES];
+    filled[..block.len()].copy_from_slice(block);
+
+    base64_encode_block(&filled, pad_count)
+}
+
+/// Returns the number of Base64 characters needed to encode byte_count bytes, including padding.
+pub fn base64_encode_len(byte_count: usize) -> usize {
     // Each 24 bit block turns 3 bytes into 4 base64 characters
     // Round up the number of blocks
-    let b64_blocks = math::ceil_div(bytes.len() * BYTE_BITS, B64_BLOCK_BITS);
-    let char_count = b64_blocks * B64_BLOCK_CHARS;
+    // Dividing by bytes rather than bits avoids overflow for large byte counts
+    let b64_blocks = math::ceil_div(byte_count, B64_BLOCK_BYTES);
+    b64_blocks * B64_BLOCK_CHARS
+}
+
+/// Encode bytes into Base64, writing the Base64 characters to the start of out.
+/// Returns the Base64 string, borrowed from out. Does not allocate.
+///
+/// If bytes is not a multiple of B64_BLOCK_BYTES long, the returned string is padded with a
+/// B64_PAD_C padding character for each missing byte.
+///
+/// Panics if out is shorter than base64_encode_len(bytes.len()).
+pub fn base64_encode_to_slice<'a>(bytes: &[u8], out: &'a mut [u8]) -> &'a str {
+    let char_count = base64_encode_len(bytes.len());
+    assert!(
+        out.len() >= char_count,
+        "Base64 output buffer is too short: {} < {}",
+        out.len(),
+        char_count
+    );
+    let out = &mut out[..char_count];
 
-    let mut s = String::with_capacity(char_count);
     let blocks = bytes.chunks(B64_BLOCK_BYTES);
-    for block in blocks {
-        if block.len() == B64_BLOCK_BYTES {
-            s.push_str(&base64_encode_block(&block, 0));
-        } else if block.len() > 0 {
-            let pad_count: usize = B64_BLOCK_BYTES - block.len();
-            let mut v = block.to_vec();
-            v.resize(B64_BLOCK_BYTES, 0);
-            s.push_str(&base64_encode_block(&v, pad_count));
+    for (block, out_block) in blocks.zip(out.chunks_mut(B64_BLOCK_CHARS)) {
+        let chars = base64_encode_partial_block(block);
+        for (c, out_c) in chars.iter().zip(out_block.iter_mut()) {
+            // Base64 characters are ASCII, so each character is one byte
+            *out_c = *c as u8;
         }
     }
 
+    str::from_utf8(out).expect("Base64 characters are ASCII")
+}
+
+/// Encode bytes into a Base64 string.
+///
+/// If bytes is not a multiple of B64_BLOCK_BYTES long, the returned string is padded with a
+/// B64_PAD_C padding character for each missing byte.
+#[cfg(feature = "alloc")]
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let char_count = base64_encode_len(bytes.len());
+
+    let mut v = vec![0; char_count];
+    base64_encode_to_slice(bytes, &mut v);
+
+    let s = String::from_utf8(v).expect("Base64 characters are ASCII");
     assert!(s.len() == char_count);
+
     s
 }
 
+/// A lazy Base64 encoder for bytes, which formats directly into a fmt::Formatter.
+///
+/// Unlike base64_encode(), displaying a Base64Display does not allocate.
+/// Honours width, fill and alignment. The precision is the maximum number of characters to
+/// display: longer Base64 strings are truncated, and end with display::ELLIPSIS.
+#[derive(Clone, Copy, Debug)]
+pub struct Base64Display<'a> {
+    /// The bytes to encode
+    bytes: &'a [u8],
+}
+
+impl<'a> Base64Display<'a> {
+    /// Create a Base64 display adapter for bytes.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Base64Display { bytes }
+    }
+}
+
+impl fmt::Display for Base64Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let char_count = base64_encode_len(self.bytes.len());
+
+        display::fmt_encoded(f, char_count, |f, shown| {
+            let chars = self
+                .bytes
+                .chunks(B64_BLOCK_BYTES)
+                .flat_map(base64_encode_partial_block);
+            for c in chars.take(shown) {
+                fmt::Write::write_char(f, c)?;
+            }
+            Ok(())
+        })
+    }
+}
+
 /// Decode a Base64 character c into its corresponding B64_CHAR_BITS bits.
 /// Panics on non-Base64 characters, including (partial) multibyte characters.
 fn base64_decode_char(c: char) -> u8 {
@@ -129,16 +209,19 @@ fn base64_decode_char(c: char) -> u8 {
     b
 }
 
-/// Decode the B64_BLOCK_CHARS Base64 characters in block, into B64_BLOCK_BYTES bytes.
+/// Decode the B64_BLOCK_CHARS Base64 characters in block, into up to B64_BLOCK_BYTES bytes.
 /// block contains the byte values of the Base64 UTF-8 characters.
 ///
+/// Returns the decoded bytes, and the number of those bytes that are valid. The remaining bytes
+/// were padding, and are zero.
+///
 /// Panics if:
 ///  * block is not B64_BLOCK_CHARS long,
 ///  * block does not have correct Base64 padding,
 ///  * block contains non-Base64 characters, including (partial) multibyte characters, or
 ///  * block is padded correctly, but the Base64 characters in block leave trailing bits in the padding
 ///    bytes.
-fn base64_decode_block(block: &[u8]) -> Vec<u8> {
+fn base64_decode_block(block: &[u8]) -> ([u8; B64_BLOCK_BYTES], usize) {
     // Require correct Base64 padding.
     // We might want to change this condition in future, to allow Base64 without padding.
     assert!(block.len() == B64_BLOCK_CHARS);
@@ -170,83 +253,172 @@ fn base64_decode_block(block: &[u8]) -> Vec<u8> {
         _ => unreachable!("block should be a 4-item slice"),
     };
 
-    let c: Vec<u8> = block
-        .iter()
-        .map(|b| base64_decode_char(*b as char))
-        .collect();
+    let mut c = [0; B64_BLOCK_CHARS];
+    for (b, c) in block.iter().zip(c.iter_mut()) {
+        *c = base64_decode_char(*b as char);
+    }
 
     let b0 = (c[0] & 0b111111) << 2 | (c[1] & 0b110000) >> 4;
     let b1 = (c[1] & 0b001111) << 4 | (c[2] & 0b111100) >> 2;
     let b2 = (c[2] & 0b000011) << 6 | (c[3] & 0b111111);
 
-    let mut v = Vec::<u8>::with_capacity(B64_BLOCK_BYTES);
-
-    v.push(b0);
-    if pad_count < 2 {
-        v.push(b1);
-    } else {
+    if pad_count >= 2 {
         assert!(
             b1 == 0,
             "Trailing Base64 bits ignored in last character due to padding"
         );
     }
-    if pad_count == 0 {
-        v.push(b2);
-    } else {
+    if pad_count >= 1 {
         assert!(
             b2 == 0,
             "Trailing Base64 bits ignored in last character due to padding"
         );
     }
 
-    assert!(v.len() > 0);
-    assert!(v.len() <= B64_BLOCK_BYTES);
-    v
+    let len = B64_BLOCK_BYTES - pad_count;
+    assert!(len > 0);
+    assert!(len <= B64_BLOCK_BYTES);
+    ([b0, b1, b2], len)
 }
 
-/// Decode a Base64 string s into bytes.
+/// Returns the maximum number of bytes decoded from char_count Base64 characters.
+///
+/// The actual number of bytes may be up to MAX_B64_PAD_CHARS less, depending on the padding.
+pub fn base64_decode_max_len(char_count: usize) -> usize {
+    // Each 24 bit block turns 4 base64 characters into 3 bytes
+    // Round up the number of blocks
+    // Dividing by characters rather than bits avoids overflow for large character counts
+    let b64_blocks = math::ceil_div(char_count, B64_BLOCK_CHARS);
+    b64_blocks * B64_BLOCK_BYTES
+}
+
+/// Decode a Base64 string s, writing the bytes to the start of out.
+/// Returns the decoded bytes, borrowed from out. Does not allocate.
 ///
 /// Panics if:
 ///  * s is not a multiple of B64_BLOCK_CHARS long,
 ///  * the final block in s does not have correct Base64 padding,
 ///  * non-terminal blocks in s have Base64 padding,
-///  * s contains non-Base64 characters, including multibyte characters, or
+///  * s contains non-Base64 characters, including multibyte characters,
 ///  * s is padded correctly, but the Base64 characters in block leave trailing bits in the padding
-///    bytes.
-pub fn base64_decode(s: &str) -> Vec<u8> {
+///    bytes, or
+///  * out is shorter than base64_decode_max_len(s.len()).
+pub fn base64_decode_to_slice<'a>(s: &str, out: &'a mut [u8]) -> &'a [u8] {
     // Base64 strings must be ASCII
     assert!(s.is_ascii(), "Invalid Base64 string");
 
-    // Each 24 bit block turns 4 base64 characters into 3 bytes
-    // Round up the number of blocks
-    let b64_blocks = math::ceil_div(s.len() * B64_CHAR_BITS, B64_BLOCK_BITS);
-    let max_byte_count = b64_blocks * B64_BLOCK_BYTES;
-    let min_byte_count = match b64_blocks {
+    let max_byte_count = base64_decode_max_len(s.len());
+    let min_byte_count = match max_byte_count {
         0 => 0,
         _ => max_byte_count - MAX_B64_PAD_CHARS,
     };
+    assert!(
+        out.len() >= max_byte_count,
+        "Base64 output buffer is too short: {} < {}",
+        out.len(),
+        max_byte_count
+    );
 
     let mut found_pad = false;
-    let mut v = Vec::<u8>::with_capacity(max_byte_count);
+    let mut byte_count = 0;
     // Since the string is ASCII, we can safely iterate over its bytes.
     let blocks = s.as_bytes().chunks(B64_BLOCK_CHARS);
     for block in blocks {
         // If we've found padding in a previous block, the Base64 is malformed
         assert!(!found_pad, "Invalid Base64 padding in mid-stream block");
 
-        let mut r = base64_decode_block(&block);
-        assert!(r.len() <= B64_BLOCK_BYTES);
-        assert!(r.len() > 0);
-        found_pad = r.len() < B64_BLOCK_BYTES;
-        v.append(&mut r);
+        let (r, len) = base64_decode_block(block);
+        assert!(len <= B64_BLOCK_BYTES);
+        assert!(len > 0);
+        found_pad = len < B64_BLOCK_BYTES;
+        out[byte_count..byte_count + len].copy_from_slice(&r[..len]);
+        byte_count += len;
     }
 
-    assert!(v.len() <= max_byte_count);
-    assert!(v.len() >= min_byte_count);
+    assert!(byte_count <= max_byte_count);
+    assert!(byte_count >= min_byte_count);
+    &out[..byte_count]
+}
+
+/// Decode a Base64 string s into bytes.
+///
+/// Panics if:
+///  * s is not a multiple of B64_BLOCK_CHARS long,
+///  * the final block in s does not have correct Base64 padding,
+///  * non-terminal blocks in s have Base64 padding,
+///  * s contains non-Base64 characters, including multibyte characters, or
+///  * s is padded correctly, but the Base64 characters in block leave trailing bits in the padding
+///    bytes.
+#[cfg(feature = "alloc")]
+pub fn base64_decode(s: &str) -> Vec<u8> {
+    // Base64 strings must be ASCII
+    assert!(s.is_ascii(), "Invalid Base64 string");
+
+    let mut v = vec![0; base64_decode_max_len(s.len())];
+    let byte_count = base64_decode_to_slice(s, &mut v).len();
+    v.truncate(byte_count);
+
     v
 }
 
+/// Decode a Base64 string s, which may be wrapped over multiple lines, into bytes.
+///
+/// ASCII whitespace is ignored, then the remaining characters are decoded using base64_decode().
+/// Panics on invalid Base64, like base64_decode().
+#[cfg(feature = "alloc")]
+pub fn base64_decode_wrapped(s: &str) -> Vec<u8> {
+    let unwrapped: String = s.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    base64_decode(&unwrapped)
+}
+
 #[cfg(test)]
+mod slice_tests {
+    use super::*;
+
+    #[test]
+    fn slices() {
+        let mut out = [0; 8];
+        assert_eq!(base64_encode_to_slice(&[0], &mut out), "AA==");
+        assert_eq!(base64_encode_to_slice(&[0, 0, 63], &mut out), "AAA/");
+        assert_eq!(
+            base64_encode_to_slice(&[255, 255, 255, 0, 0], &mut out),
+            "////AAA="
+        );
+        assert_eq!(base64_encode_to_slice(&[], &mut out), "");
+
+        let mut out = [0; 6];
+        assert_eq!(
+            base64_decode_to_slice("////AAAA", &mut out),
+            [255, 255, 255, 0, 0, 0]
+        );
+        assert_eq!(base64_decode_to_slice("AAg=", &mut out), [0, 32 >> 2]);
+        assert_eq!(base64_decode_to_slice("gA==", &mut out), [32 << 2]);
+        assert_eq!(base64_decode_to_slice("", &mut out), []);
+
+        assert_eq!(base64_encode_len(0), 0);
+        assert_eq!(base64_encode_len(1), 4);
+        assert_eq!(base64_encode_len(3), 4);
+        assert_eq!(base64_encode_len(4), 8);
+        assert_eq!(base64_decode_max_len(0), 0);
+        assert_eq!(base64_decode_max_len(4), 3);
+        assert_eq!(base64_decode_max_len(8), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Base64 output buffer is too short")]
+    fn invalid_base64_encode_slice_too_short() {
+        base64_encode_to_slice(&[0], &mut [0; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Base64 output buffer is too short")]
+    fn invalid_base64_decode_slice_too_short() {
+        // The padding is only checked after the buffer length
+        base64_decode_to_slice("AA==", &mut [0; 2]);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
@@ -304,6 +476,43 @@ mod tests {
         assert_eq!(base64_encode(&base64_decode(test_vector)), test_vector);
     }
 
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", Base64Display::new(&[])), "");
+        assert_eq!(format!("{}", Base64Display::new(&[0])), "AA==");
+        assert_eq!(format!("{}", Base64Display::new(&[0, 0])), "AAA=");
+        assert_eq!(
+            format!("{}", Base64Display::new(&[255, 255, 255, 0, 0, 0])),
+            "////AAAA"
+        );
+        assert_eq!(
+            format!("{}", Base64Display::new(&[1 << 2, 0, 0, 0, 0, 2, 32])),
+            base64_encode(&[1 << 2, 0, 0, 0, 0, 2, 32])
+        );
+    }
+
+    #[test]
+    fn display_width() {
+        assert_eq!(format!("{:6}", Base64Display::new(&[0])), "AA==  ");
+        assert_eq!(format!("{:>6}", Base64Display::new(&[0])), "  AA==");
+        assert_eq!(format!("{:-^8}", Base64Display::new(&[0])), "--AA==--");
+        // Width is a minimum
+        assert_eq!(format!("{:2}", Base64Display::new(&[0])), "AA==");
+    }
+
+    #[test]
+    fn display_precision() {
+        let bytes = [255, 255, 255, 0, 0, 0, 0];
+        assert_eq!(
+            format!("{:.12}", Base64Display::new(&bytes)),
+            "////AAAAAA=="
+        );
+        assert_eq!(format!("{:.11}", Base64Display::new(&bytes)), "////AAAA...");
+        assert_eq!(format!("{:.4}", Base64Display::new(&bytes)), "/...");
+        assert_eq!(format!("{:.2}", Base64Display::new(&bytes)), "..");
+        assert_eq!(format!("{:<9.6}", Base64Display::new(&bytes)), "///...   ");
+    }
+
     #[test]
     #[should_panic(expected = "Invalid Base64 character")]
     fn invalid_base64_char_before_plus() {
@@ -436,5 +645,19 @@ mod tests {
         base64_decode("gAC=");
     }
 
+    #[test]
+    fn wrapped() {
+        assert_eq!(base64_decode_wrapped(""), []);
+        assert_eq!(base64_decode_wrapped("\n"), []);
+        assert_eq!(base64_decode_wrapped("AAAA\nAAA=\n"), [0, 0, 0, 0, 0]);
+        assert_eq!(base64_decode_wrapped("AA\r\nAA\tAA A=\n"), [0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Base64 padding in mid-stream block")]
+    fn invalid_wrapped_pad_mid_stream() {
+        base64_decode_wrapped("AAA=\nAAAA\n");
+    }
+
     // Encoding out-of-range integers won't compile
 }
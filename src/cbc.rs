@@ -0,0 +1,223 @@
+//! Cipher Block Chaining mode, built from single-block encryption and XOR
+//!
+//! Unlike the generic modes in the cipher module, these functions call a hook after each block,
+//! so attacks can observe the intermediate state.
+
+#![deny(missing_docs)]
+
+use alloc::vec::Vec;
+
+use crate::cipher::{check_lengths, BlockCipher, Mode, ModeError};
+use crate::xor::fixed_xor_in_place;
+
+/// The state of a single CBC block, after it has been encrypted or decrypted.
+///
+/// For block i:
+///  * intermediate = plaintext XOR previous, and
+///  * ciphertext = encrypt_block(intermediate).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CbcBlock<'a> {
+    /// The index of the block, starting at zero
+    pub index: usize,
+    /// The previous ciphertext block, or the IV for the first block
+    pub previous: &'a [u8],
+    /// The block cipher input when encrypting, or its output when decrypting
+    pub intermediate: &'a [u8],
+    /// The plaintext block
+    pub plaintext: &'a [u8],
+    /// The ciphertext block
+    pub ciphertext: &'a [u8],
+}
+
+/// Returns plaintext encrypted using cipher in CBC mode, with initialisation vector iv.
+/// Calls hook with the state of each block, in order.
+///
+/// Returns an error if iv is not one block long, or plaintext is not a whole number of blocks.
+pub fn cbc_encrypt_with_hook<C, F>(
+    cipher: &C,
+    iv: &[u8],
+    plaintext: &[u8],
+    mut hook: F,
+) -> Result<Vec<u8>, ModeError>
+where
+    C: BlockCipher + ?Sized,
+    F: FnMut(&CbcBlock),
+{
+    let block_size = check_lengths(cipher, Mode::Cbc, iv, plaintext)?;
+
+    let mut ciphertext = Vec::with_capacity(plaintext.len());
+    let mut previous = iv.to_vec();
+    for (index, plaintext_block) in plaintext.chunks_exact(block_size).enumerate() {
+        let mut intermediate = plaintext_block.to_vec();
+        fixed_xor_in_place(&mut intermediate, &previous).expect("blocks are the same length");
+
+        let mut ciphertext_block = intermediate.clone();
+        cipher.encrypt_block(&mut ciphertext_block);
+
+        hook(&CbcBlock {
+            index,
+            previous: &previous,
+            intermediate: &intermediate,
+            plaintext: plaintext_block,
+            ciphertext: &ciphertext_block,
+        });
+
+        ciphertext.extend_from_slice(&ciphertext_block);
+        previous = ciphertext_block;
+    }
+
+    Ok(ciphertext)
+}
+
+/// Returns ciphertext decrypted using cipher in CBC mode, with initialisation vector iv.
+/// Calls hook with the state of each block, in order.
+///
+/// Returns an error if iv is not one block long, or ciphertext is not a whole number of blocks.
+pub fn cbc_decrypt_with_hook<C, F>(
+    cipher: &C,
+    iv: &[u8],
+    ciphertext: &[u8],
+    mut hook: F,
+) -> Result<Vec<u8>, ModeError>
+where
+    C: BlockCipher + ?Sized,
+    F: FnMut(&CbcBlock),
+{
+    let block_size = check_lengths(cipher, Mode::Cbc, iv, ciphertext)?;
+
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    let mut previous = iv;
+    for (index, ciphertext_block) in ciphertext.chunks_exact(block_size).enumerate() {
+        let mut intermediate = ciphertext_block.to_vec();
+        cipher.decrypt_block(&mut intermediate);
+
+        let mut plaintext_block = intermediate.clone();
+        fixed_xor_in_place(&mut plaintext_block, previous).expect("blocks are the same length");
+
+        hook(&CbcBlock {
+            index,
+            previous,
+            intermediate: &intermediate,
+            plaintext: &plaintext_block,
+            ciphertext: ciphertext_block,
+        });
+
+        plaintext.extend_from_slice(&plaintext_block);
+        previous = ciphertext_block;
+    }
+
+    Ok(plaintext)
+}
+
+/// Returns plaintext encrypted using cipher in CBC mode, with initialisation vector iv.
+///
+/// Returns an error if iv is not one block long, or plaintext is not a whole number of blocks.
+pub fn cbc_encrypt<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    iv: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, ModeError> {
+    cbc_encrypt_with_hook(cipher, iv, plaintext, |_| {})
+}
+
+/// Returns ciphertext decrypted using cipher in CBC mode, with initialisation vector iv.
+///
+/// Returns an error if iv is not one block long, or ciphertext is not a whole number of blocks.
+pub fn cbc_decrypt<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ModeError> {
+    cbc_decrypt_with_hook(cipher, iv, ciphertext, |_| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::Aes;
+    use crate::cipher::{self, Mode};
+    use crate::hex::hex_decode;
+    use crate::test_util::{
+        sp_800_38a_ciphertext, SP_800_38A_IV, SP_800_38A_KEY, SP_800_38A_PLAINTEXT,
+    };
+    use crate::xor::fixed_xor;
+
+    #[test]
+    fn sp_800_38a() {
+        let aes = Aes::new(&hex_decode(SP_800_38A_KEY)).expect("key is valid");
+        let iv = hex_decode(SP_800_38A_IV);
+        let plaintext = hex_decode(SP_800_38A_PLAINTEXT);
+        let ciphertext = sp_800_38a_ciphertext(Mode::Cbc);
+
+        assert_eq!(cbc_encrypt(&aes, &iv, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(cbc_decrypt(&aes, &iv, &ciphertext), Ok(plaintext.clone()));
+    }
+
+    #[test]
+    fn matches_generic_mode() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        let iv = [0x42; 16];
+        let plaintext: Vec<u8> = (0..80).collect();
+
+        let ciphertext = cbc_encrypt(&aes, &iv, &plaintext).expect("lengths are valid");
+        assert_eq!(
+            cipher::encrypt(&aes, Mode::Cbc, &iv, &plaintext),
+            Ok(ciphertext.clone())
+        );
+        assert_eq!(cbc_decrypt(&aes, &iv, &ciphertext), Ok(plaintext));
+        assert_eq!(cbc_encrypt(&aes, &iv, &[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn hooks() {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        let iv = [7; 16];
+        let plaintext: Vec<u8> = (0..48).collect();
+
+        let mut encrypted = Vec::new();
+        let ciphertext = cbc_encrypt_with_hook(&aes, &iv, &plaintext, |block| {
+            assert_eq!(
+                fixed_xor(block.plaintext, block.previous),
+                Ok(block.intermediate.to_vec())
+            );
+            encrypted.push((block.index, block.intermediate.to_vec()));
+        })
+        .expect("lengths are valid");
+
+        let mut decrypted = Vec::new();
+        cbc_decrypt_with_hook(&aes, &iv, &ciphertext, |block| {
+            let expected_previous = match block.index {
+                0 => &iv[..],
+                i => &ciphertext[(i - 1) * 16..i * 16],
+            };
+            assert_eq!(block.previous, expected_previous);
+            assert_eq!(block.ciphertext, &ciphertext[block.index * 16..][..16]);
+            decrypted.push((block.index, block.intermediate.to_vec()));
+        })
+        .expect("lengths are valid");
+
+        // The intermediate state is the same in both directions
+        assert_eq!(encrypted.len(), 3);
+        assert_eq!(encrypted, decrypted);
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let aes = Aes::new(&[0; 16]).expect("key is valid");
+        assert_eq!(
+            cbc_encrypt(&aes, &[0; 15], &[0; 16]),
+            Err(ModeError::IvLength {
+                len: 15,
+                expected: 16
+            })
+        );
+        assert_eq!(
+            cbc_decrypt(&aes, &[0; 16], &[0; 17]),
+            Err(ModeError::PartialBlock {
+                len: 17,
+                block_size: 16
+            })
+        );
+    }
+}
@@ -0,0 +1,613 @@
+//! Block ciphers, and block cipher modes of operation, from NIST SP 800-38A
+//!
+//! ECB and CBC only encrypt whole blocks. CFB, OFB, and CTR are stream modes, which encrypt data
+//! of any length.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::ctr::{Ctr, CtrLayout};
+#[cfg(feature = "alloc")]
+use crate::padding::Padding;
+use crate::padding::PaddingError;
+
+/// The largest block size supported by the modes of operation, in bytes
+pub const MAX_BLOCK_BYTES: usize = 32;
+
+/// A block cipher, with an expanded key.
+pub trait BlockCipher {
+    /// Returns the block size, in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Encrypt block in place.
+    ///
+    /// Panics if block is not block_size() bytes long.
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypt block in place.
+    ///
+    /// Panics if block is not block_size() bytes long.
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+impl<C: BlockCipher + ?Sized> BlockCipher for &C {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        (**self).encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        (**self).decrypt_block(block)
+    }
+}
+
+/// A block cipher mode of operation
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Electronic Codebook: each block is encrypted independently
+    Ecb,
+    /// Cipher Block Chaining: each plaintext block is XORed with the previous ciphertext block
+    Cbc,
+    /// Cipher Feedback: the previous ciphertext block is encrypted to make the keystream
+    Cfb,
+    /// Output Feedback: the previous keystream block is encrypted to make the keystream
+    Ofb,
+    /// Counter: an incrementing counter block is encrypted to make the keystream
+    Ctr,
+}
+
+impl Mode {
+    /// Every mode of operation
+    pub const ALL: [Mode; 5] = [Mode::Ecb, Mode::Cbc, Mode::Cfb, Mode::Ofb, Mode::Ctr];
+
+    /// Returns the initialisation vector length for this mode, for a cipher with block_size.
+    /// For CTR mode, the IV is the initial counter block.
+    pub fn iv_len(self, block_size: usize) -> usize {
+        match self {
+            Mode::Ecb => 0,
+            Mode::Cbc | Mode::Cfb | Mode::Ofb | Mode::Ctr => block_size,
+        }
+    }
+
+    /// Returns true if this mode encrypts data of any length, and false if it only encrypts
+    /// whole blocks.
+    pub fn is_stream(self) -> bool {
+        match self {
+            Mode::Ecb | Mode::Cbc => false,
+            Mode::Cfb | Mode::Ofb | Mode::Ctr => true,
+        }
+    }
+}
+
+/// The reasons a mode of operation can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModeError {
+    /// The data is not a whole number of blocks, in a block mode
+    PartialBlock {
+        /// The length of the data
+        len: usize,
+        /// The cipher block size
+        block_size: usize,
+    },
+    /// The initialisation vector is the wrong length
+    IvLength {
+        /// The length of the initialisation vector
+        len: usize,
+        /// The expected length
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModeError::PartialBlock { len, block_size } => write!(
+                f,
+                "Data must be a multiple of the {} byte block size, but it is {} bytes long",
+                block_size, len
+            ),
+            ModeError::IvLength { len, expected } => write!(
+                f,
+                "The initialisation vector must be {} bytes long, but it is {} bytes long",
+                expected, len
+            ),
+        }
+    }
+}
+
+/// The reasons padded encryption or decryption can fail.
+///
+/// Padding errors are distinct from mode errors, so padding oracles can distinguish them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CipherError {
+    /// The mode of operation failed, before decryption
+    Mode(ModeError),
+    /// The decrypted data has invalid padding
+    Padding(PaddingError),
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CipherError::Mode(e) => write!(f, "{}", e),
+            CipherError::Padding(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<ModeError> for CipherError {
+    fn from(e: ModeError) -> Self {
+        CipherError::Mode(e)
+    }
+}
+
+impl From<PaddingError> for CipherError {
+    fn from(e: PaddingError) -> Self {
+        CipherError::Padding(e)
+    }
+}
+
+/// Returns the block size of cipher.
+///
+/// Panics if the block size is zero, or larger than MAX_BLOCK_BYTES.
+fn checked_block_size<C: BlockCipher + ?Sized>(cipher: &C) -> usize {
+    let block_size = cipher.block_size();
+    assert!(
+        block_size > 0 && block_size <= MAX_BLOCK_BYTES,
+        "Block size must be between 1 and MAX_BLOCK_BYTES"
+    );
+
+    block_size
+}
+
+/// Check the lengths of iv and data for mode, using cipher's block size.
+/// Returns the block size.
+///
+/// Panics if the block size is zero, or larger than MAX_BLOCK_BYTES.
+pub(crate) fn check_lengths<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<usize, ModeError> {
+    let block_size = checked_block_size(cipher);
+
+    let expected = mode.iv_len(block_size);
+    if iv.len() != expected {
+        return Err(ModeError::IvLength {
+            len: iv.len(),
+            expected,
+        });
+    }
+
+    if !mode.is_stream() && !data.len().is_multiple_of(block_size) {
+        return Err(ModeError::PartialBlock {
+            len: data.len(),
+            block_size,
+        });
+    }
+
+    Ok(block_size)
+}
+
+/// XOR src into dest. Both slices must be the same length.
+fn xor_into(dest: &mut [u8], src: &[u8]) {
+    debug_assert_eq!(dest.len(), src.len());
+    for (d, s) in dest.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+/// XOR data with the keystream for the feedback modes.
+///
+/// After each block, next_input is called with the input block, the keystream block, and the
+/// ciphertext block. It updates the input block for the next block.
+/// The final ciphertext block may be partial.
+fn stream_xor<C, F>(cipher: &C, iv: &[u8], data: &mut [u8], decrypt: bool, mut next_input: F)
+where
+    C: BlockCipher + ?Sized,
+    F: FnMut(&mut [u8], &[u8], &[u8]),
+{
+    let block_size = iv.len();
+    let mut input_buf = [0; MAX_BLOCK_BYTES];
+    let input = &mut input_buf[..block_size];
+    input.copy_from_slice(iv);
+    let mut keystream_buf = [0; MAX_BLOCK_BYTES];
+    let mut ciphertext_buf = [0; MAX_BLOCK_BYTES];
+
+    for chunk in data.chunks_mut(block_size) {
+        let keystream = &mut keystream_buf[..block_size];
+        keystream.copy_from_slice(input);
+        cipher.encrypt_block(keystream);
+
+        let ciphertext = &mut ciphertext_buf[..chunk.len()];
+        if decrypt {
+            ciphertext.copy_from_slice(chunk);
+        }
+        xor_into(chunk, &keystream[..chunk.len()]);
+        if !decrypt {
+            ciphertext.copy_from_slice(chunk);
+        }
+
+        next_input(input, keystream, ciphertext);
+    }
+}
+
+/// Encrypt data in place using cipher in mode, with initialisation vector iv.
+/// Does not allocate.
+///
+/// Returns an error if iv is the wrong length for mode, or if mode is a block mode and data is
+/// not a whole number of blocks. Data is unchanged on error.
+pub fn encrypt_in_place<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &mut [u8],
+) -> Result<(), ModeError> {
+    let block_size = check_lengths(cipher, mode, iv, data)?;
+
+    match mode {
+        Mode::Ecb => {
+            for block in data.chunks_exact_mut(block_size) {
+                cipher.encrypt_block(block);
+            }
+        }
+        Mode::Cbc => {
+            let mut previous_buf = [0; MAX_BLOCK_BYTES];
+            let previous = &mut previous_buf[..block_size];
+            previous.copy_from_slice(iv);
+            for block in data.chunks_exact_mut(block_size) {
+                xor_into(block, previous);
+                cipher.encrypt_block(block);
+                previous.copy_from_slice(block);
+            }
+        }
+        Mode::Cfb | Mode::Ofb | Mode::Ctr => stream_apply(cipher, mode, iv, data, false),
+    }
+
+    Ok(())
+}
+
+/// Decrypt data in place using cipher in mode, with initialisation vector iv.
+/// Does not allocate.
+///
+/// Returns an error if iv is the wrong length for mode, or if mode is a block mode and data is
+/// not a whole number of blocks. Data is unchanged on error.
+pub fn decrypt_in_place<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &mut [u8],
+) -> Result<(), ModeError> {
+    let block_size = check_lengths(cipher, mode, iv, data)?;
+
+    match mode {
+        Mode::Ecb => {
+            for block in data.chunks_exact_mut(block_size) {
+                cipher.decrypt_block(block);
+            }
+        }
+        Mode::Cbc => {
+            let mut previous_buf = [0; MAX_BLOCK_BYTES];
+            let previous = &mut previous_buf[..block_size];
+            previous.copy_from_slice(iv);
+            let mut ciphertext_buf = [0; MAX_BLOCK_BYTES];
+            let ciphertext = &mut ciphertext_buf[..block_size];
+            for block in data.chunks_exact_mut(block_size) {
+                ciphertext.copy_from_slice(block);
+                cipher.decrypt_block(block);
+                xor_into(block, previous);
+                previous.copy_from_slice(ciphertext);
+            }
+        }
+        Mode::Cfb | Mode::Ofb | Mode::Ctr => stream_apply(cipher, mode, iv, data, true),
+    }
+
+    Ok(())
+}
+
+/// Encrypt or decrypt data in place, using a stream mode.
+fn stream_apply<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &mut [u8],
+    decrypt: bool,
+) {
+    match mode {
+        // The last ciphertext block may be partial, but there are no more blocks after it
+        Mode::Cfb => stream_xor(cipher, iv, data, decrypt, |input, _, ciphertext| {
+            input[..ciphertext.len()].copy_from_slice(ciphertext)
+        }),
+        Mode::Ofb => stream_xor(cipher, iv, data, decrypt, |input, keystream, _| {
+            input.copy_from_slice(keystream)
+        }),
+        // Encryption and decryption are the same in counter mode
+        Mode::Ctr => Ctr::with_counter(cipher, CtrLayout::full_block_be(iv.len()), &[], iv)
+            .expect("lengths are checked")
+            .apply_keystream_at(0, data),
+        Mode::Ecb | Mode::Cbc => unreachable!("{:?} is not a stream mode", mode),
+    }
+}
+
+/// Returns data encrypted using cipher in mode, with initialisation vector iv.
+///
+/// Returns an error if iv is the wrong length for mode, or if mode is a block mode and data is
+/// not a whole number of blocks.
+#[cfg(feature = "alloc")]
+pub fn encrypt<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, ModeError> {
+    let mut v = data.to_vec();
+    encrypt_in_place(cipher, mode, iv, &mut v)?;
+
+    Ok(v)
+}
+
+/// Returns data decrypted using cipher in mode, with initialisation vector iv.
+///
+/// Returns an error if iv is the wrong length for mode, or if mode is a block mode and data is
+/// not a whole number of blocks.
+#[cfg(feature = "alloc")]
+pub fn decrypt<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, ModeError> {
+    let mut v = data.to_vec();
+    decrypt_in_place(cipher, mode, iv, &mut v)?;
+
+    Ok(v)
+}
+
+/// Returns data padded using padding, then encrypted using cipher in mode, with initialisation
+/// vector iv.
+///
+/// Returns a mode error if iv is the wrong length for mode. Returns a padding error if padding
+/// is Iso10126, which needs random bytes. Use Padding::pad_with() and encrypt() instead.
+#[cfg(feature = "alloc")]
+pub fn encrypt_padded<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    padding: Padding,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    let block_size = checked_block_size(cipher);
+    let mut v = padding.pad(data, block_size)?;
+    encrypt_in_place(cipher, mode, iv, &mut v)?;
+
+    Ok(v)
+}
+
+/// Returns data decrypted using cipher in mode, with initialisation vector iv, then unpadded
+/// using padding.
+///
+/// Returns a mode error if iv is the wrong length for mode, or if mode is a block mode and data
+/// is not a whole number of blocks. Returns a padding error if the decrypted padding is invalid.
+#[cfg(feature = "alloc")]
+pub fn decrypt_padded<C: BlockCipher + ?Sized>(
+    cipher: &C,
+    mode: Mode,
+    padding: Padding,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    let mut v = decrypt(cipher, mode, iv, data)?;
+    let len = padding.unpad(&v, checked_block_size(cipher))?.len();
+    v.truncate(len);
+
+    Ok(v)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use crate::aes::{Aes, AesBitsliced};
+    use crate::hex::hex_decode;
+    use crate::test_util::{
+        sp_800_38a_iv, SP_800_38A_CIPHERTEXTS, SP_800_38A_IV, SP_800_38A_KEY, SP_800_38A_PLAINTEXT,
+    };
+
+    #[test]
+    fn sp_800_38a() {
+        let key = hex_decode(SP_800_38A_KEY);
+        let table = Aes::new(&key).expect("key is valid");
+        let bitsliced = AesBitsliced::new(&key).expect("key is valid");
+        let ciphers: [&dyn BlockCipher; 2] = [&table, &bitsliced];
+        let plaintext = hex_decode(SP_800_38A_PLAINTEXT);
+
+        for cipher in ciphers {
+            for (mode, ciphertext) in SP_800_38A_CIPHERTEXTS {
+                let iv = sp_800_38a_iv(mode);
+                let ciphertext = hex_decode(ciphertext);
+                assert_eq!(
+                    encrypt(cipher, mode, &iv, &plaintext),
+                    Ok(ciphertext.clone()),
+                    "{:?}",
+                    mode
+                );
+                assert_eq!(
+                    decrypt(cipher, mode, &iv, &ciphertext),
+                    Ok(plaintext.clone()),
+                    "{:?}",
+                    mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stream_partial_blocks() {
+        let aes = Aes::new(&hex_decode(SP_800_38A_KEY)).expect("key is valid");
+        let plaintext = hex_decode(SP_800_38A_PLAINTEXT);
+
+        for (mode, ciphertext) in SP_800_38A_CIPHERTEXTS {
+            if !mode.is_stream() {
+                continue;
+            }
+
+            // Stream modes truncate the keystream
+            let iv = sp_800_38a_iv(mode);
+            let ciphertext = hex_decode(ciphertext);
+            for len in [0, 1, 15, 17, 40, 63] {
+                assert_eq!(
+                    encrypt(&aes, mode, &iv, &plaintext[..len]),
+                    Ok(ciphertext[..len].to_vec()),
+                    "{:?}",
+                    mode
+                );
+                assert_eq!(
+                    decrypt(&aes, mode, &iv, &ciphertext[..len]),
+                    Ok(plaintext[..len].to_vec()),
+                    "{:?}",
+                    mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(Mode::Ecb.iv_len(16), 0);
+        assert_eq!(Mode::Ctr.iv_len(16), 16);
+        assert_eq!(Mode::ALL.iter().filter(|mode| mode.is_stream()).count(), 3);
+    }
+
+    #[test]
+    fn padded() {
+        let aes = Aes::new(&hex_decode(SP_800_38A_KEY)).expect("key is valid");
+        let iv = hex_decode(SP_800_38A_IV);
+
+        for mode in [Mode::Cbc, Mode::Ofb] {
+            for padding in [
+                Padding::Pkcs7,
+                Padding::AnsiX923,
+                Padding::Iso7816_4,
+                Padding::Zero,
+            ] {
+                for len in [0, 1, 15, 16, 17] {
+                    let plaintext = vec![b'a'; len];
+                    let ciphertext = encrypt_padded(&aes, mode, padding, &iv, &plaintext)
+                        .expect("lengths are valid");
+                    assert_eq!(ciphertext.len(), len + padding.pad_len(len, 16));
+                    assert_eq!(
+                        decrypt_padded(&aes, mode, padding, &iv, &ciphertext),
+                        Ok(plaintext)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn padded_errors() {
+        let aes = Aes::new(&[0; 16]).expect("key is valid");
+        let iv = [0; 16];
+
+        // Padding errors happen after decryption
+        let ciphertext = encrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, &iv, b"abc")
+            .expect("lengths are valid");
+        let mut flipped_iv = iv;
+        flipped_iv[14] ^= 1;
+        assert_eq!(
+            decrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, &flipped_iv, &ciphertext),
+            Err(CipherError::Padding(PaddingError::InvalidPadByte {
+                index: 14,
+                byte: 0x0c,
+                expected: 0x0d
+            }))
+        );
+
+        // Random padding needs an RNG
+        assert_eq!(
+            encrypt_padded(&aes, Mode::Cbc, Padding::Iso10126, &iv, b"abc"),
+            Err(CipherError::Padding(PaddingError::NeedsRng))
+        );
+
+        // Mode errors happen before decryption
+        assert_eq!(
+            decrypt_padded(&aes, Mode::Cbc, Padding::Pkcs7, &iv, &[0; 15]),
+            Err(CipherError::Mode(ModeError::PartialBlock {
+                len: 15,
+                block_size: 16
+            }))
+        );
+        assert_eq!(
+            format!("{}", CipherError::Padding(PaddingError::MissingMarker)),
+            "Padding must contain a 0x80 marker byte"
+        );
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let aes = Aes::new(&[0; 16]).expect("key is valid");
+
+        assert_eq!(
+            encrypt(&aes, Mode::Ecb, &[], &[0; 17]),
+            Err(ModeError::PartialBlock {
+                len: 17,
+                block_size: 16
+            })
+        );
+        assert_eq!(
+            decrypt(&aes, Mode::Cbc, &[0; 16], &[0; 15]),
+            Err(ModeError::PartialBlock {
+                len: 15,
+                block_size: 16
+            })
+        );
+        assert_eq!(
+            encrypt(&aes, Mode::Ecb, &[0; 16], &[0; 16]),
+            Err(ModeError::IvLength {
+                len: 16,
+                expected: 0
+            })
+        );
+        assert_eq!(
+            decrypt(&aes, Mode::Ctr, &[0; 8], &[0; 16]),
+            Err(ModeError::IvLength {
+                len: 8,
+                expected: 16
+            })
+        );
+
+        // Data is unchanged on error
+        let mut data = [1; 17];
+        assert!(encrypt_in_place(&aes, Mode::Cbc, &[0; 16], &mut data).is_err());
+        assert_eq!(data, [1; 17]);
+
+        assert_eq!(
+            format!(
+                "{}",
+                ModeError::PartialBlock {
+                    len: 17,
+                    block_size: 16
+                }
+            ),
+            "Data must be a multiple of the 16 byte block size, but it is 17 bytes long"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                ModeError::IvLength {
+                    len: 8,
+                    expected: 16
+                }
+            ),
+            "The initialisation vector must be 16 bytes long, but it is 8 bytes long"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Structured cookies: ordered key=value pairs, separated by '&'
+//!
+//! Encoding strips '&' and '=' from keys and values, so a value can not add extra pairs.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The separator between pairs
+pub const PAIR_SEPARATOR: char = '&';
+/// The separator between a key and its value
+pub const KEY_VALUE_SEPARATOR: char = '=';
+
+/// The user ID in every profile from profile_for()
+pub const PROFILE_UID: u32 = 10;
+/// The role in every profile from profile_for()
+pub const PROFILE_ROLE: &str = "user";
+
+/// The reasons a cookie can be invalid
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CookieError {
+    /// The pair at index does not contain a KEY_VALUE_SEPARATOR
+    MissingSeparator {
+        /// The index of the pair, starting at zero
+        index: usize,
+    },
+    /// The cookie is not valid UTF-8
+    InvalidUtf8,
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CookieError::MissingSeparator { index } => write!(
+                f,
+                "Cookie pair {} is missing a '{}' separator",
+                index, KEY_VALUE_SEPARATOR
+            ),
+            CookieError::InvalidUtf8 => write!(f, "Cookie is not valid UTF-8"),
+        }
+    }
+}
+
+/// An ordered map of cookie keys to values
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cookie {
+    /// The pairs, in insertion order, with unique keys
+    pairs: Vec<(String, String)>,
+}
+
+impl Cookie {
+    /// Create an empty cookie.
+    pub fn new() -> Self {
+        Cookie::default()
+    }
+
+    /// Returns the cookie parsed from s, which is a series of key=value pairs separated by '&'.
+    /// The first '=' in each pair separates the key from its value. If a key repeats, the last
+    /// value is kept, in the position of the first.
+    ///
+    /// Returns an error if a pair does not contain '='. An empty string is an empty cookie.
+    pub fn parse(s: &str) -> Result<Self, CookieError> {
+        let mut cookie = Cookie::new();
+        if s.is_empty() {
+            return Ok(cookie);
+        }
+
+        for (index, pair) in s.split(PAIR_SEPARATOR).enumerate() {
+            let (key, value) = pair
+                .split_once(KEY_VALUE_SEPARATOR)
+                .ok_or(CookieError::MissingSeparator { index })?;
+            cookie.insert(key, value);
+        }
+
+        Ok(cookie)
+    }
+
+    /// Returns the cookie parsed from UTF-8 bytes.
+    ///
+    /// Returns an error if bytes are not valid UTF-8, or are not a valid cookie.
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Self, CookieError> {
+        let s = core::str::from_utf8(bytes).map_err(|_| CookieError::InvalidUtf8)?;
+        Cookie::parse(s)
+    }
+
+    /// Set key to value. A new key is added at the end, and an existing key keeps its position.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        match self.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.into(),
+            None => self.pairs.push((key.into(), value.into())),
+        }
+    }
+
+    /// Returns the value for key, or None if key is not in the cookie.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns true if the cookie has no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns an iterator over the keys and values, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns the cookie encoded as key=value pairs separated by '&', with '&' and '=' stripped
+    /// from every key and value.
+    pub fn encode(&self) -> String {
+        let mut encoded = String::new();
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                encoded.push(PAIR_SEPARATOR);
+            }
+            encoded.push_str(&strip_separators(key));
+            encoded.push(KEY_VALUE_SEPARATOR);
+            encoded.push_str(&strip_separators(value));
+        }
+
+        encoded
+    }
+}
+
+/// Returns s without any '&' or '=' characters.
+pub fn strip_separators(s: &str) -> String {
+    s.chars()
+        .filter(|&c| c != PAIR_SEPARATOR && c != KEY_VALUE_SEPARATOR)
+        .collect()
+}
+
+/// Returns a user profile for email, with a fixed uid and role.
+///
+/// The email is stripped of '&' and '=' when the profile is encoded.
+pub fn profile_for(email: &str) -> Cookie {
+    let mut cookie = Cookie::new();
+    cookie.insert("email", email);
+    cookie.insert("uid", &PROFILE_UID.to_string());
+    cookie.insert("role", PROFILE_ROLE);
+    cookie
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let cookie = Cookie::parse("foo=bar&baz=qux&zap=zazzle").expect("cookie is valid");
+        assert_eq!(
+            cookie.iter().collect::<Vec<_>>(),
+            [("foo", "bar"), ("baz", "qux"), ("zap", "zazzle")]
+        );
+        assert_eq!(cookie.get("baz"), Some("qux"));
+        assert_eq!(cookie.get("qux"), None);
+        assert_eq!(cookie.encode(), "foo=bar&baz=qux&zap=zazzle");
+
+        // Repeated keys keep their first position, and the first '=' splits each pair
+        let cookie = Cookie::parse("a=1&b=&a=2=3").expect("cookie is valid");
+        assert_eq!(cookie.iter().collect::<Vec<_>>(), [("a", "2=3"), ("b", "")]);
+
+        assert_eq!(Cookie::parse(""), Ok(Cookie::new()));
+        assert!(Cookie::new().is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Cookie::parse("foo=bar&baz"),
+            Err(CookieError::MissingSeparator { index: 1 })
+        );
+        assert_eq!(
+            Cookie::parse("foo=bar&"),
+            Err(CookieError::MissingSeparator { index: 1 })
+        );
+        assert_eq!(
+            Cookie::parse_bytes(b"foo=\xff"),
+            Err(CookieError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn profile() {
+        let profile = profile_for("foo@bar.com");
+        assert_eq!(profile.len(), 3);
+        assert_eq!(profile.encode(), "email=foo@bar.com&uid=10&role=user");
+        assert_eq!(Cookie::parse(&profile.encode()), Ok(profile));
+
+        // Separators can not add pairs
+        let profile = profile_for("foo@bar.com&role=admin");
+        assert_eq!(
+            profile.encode(),
+            "email=foo@bar.comroleadmin&uid=10&role=user"
+        );
+        let parsed = Cookie::parse(&profile.encode()).expect("cookie is valid");
+        assert_eq!(parsed.get("role"), Some(PROFILE_ROLE));
+    }
+}
@@ -0,0 +1,472 @@
+//! Counter mode, with a configurable counter block layout
+//!
+//! Each counter block is a fixed nonce followed by a block counter, which starts at zero, or at a
+//! chosen initial value. The keystream can be generated at any byte offset, so a stream can be
+//! encrypted in pieces, or modified in place. The generic CTR mode uses a big-endian counter which
+//! fills the whole block.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use crate::cipher::{BlockCipher, MAX_BLOCK_BYTES};
+
+/// The byte order of the block counter
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Endian {
+    /// Most significant byte first
+    Big,
+    /// Least significant byte first
+    Little,
+}
+
+/// The layout of a counter block: a nonce, followed by a block counter
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CtrLayout {
+    /// The length of the nonce, in bytes
+    pub nonce_len: usize,
+    /// The width of the block counter, in bytes
+    pub counter_len: usize,
+    /// The byte order of the block counter
+    pub counter_endian: Endian,
+}
+
+impl CtrLayout {
+    /// A 64-bit nonce, followed by a 64-bit little-endian counter, for 128-bit blocks
+    pub const NONCE_64_COUNTER_64_LE: CtrLayout = CtrLayout {
+        nonce_len: 8,
+        counter_len: 8,
+        counter_endian: Endian::Little,
+    };
+
+    /// A 96-bit nonce, followed by a 32-bit big-endian counter, for 128-bit blocks
+    pub const NONCE_96_COUNTER_32_BE: CtrLayout = CtrLayout {
+        nonce_len: 12,
+        counter_len: 4,
+        counter_endian: Endian::Big,
+    };
+
+    /// Returns a layout with a big-endian counter which fills a block of block_size bytes, and no
+    /// nonce. This is the counter block in NIST SP 800-38A.
+    pub const fn full_block_be(block_size: usize) -> CtrLayout {
+        CtrLayout {
+            nonce_len: 0,
+            counter_len: block_size,
+            counter_endian: Endian::Big,
+        }
+    }
+
+    /// Returns the length of a counter block, in bytes.
+    pub fn block_size(&self) -> usize {
+        self.nonce_len + self.counter_len
+    }
+
+    /// Returns the number of blocks before the counter wraps, or None if it is more than u64::MAX.
+    pub fn max_blocks(&self) -> Option<u64> {
+        if self.counter_len < 8 {
+            Some(1 << (8 * self.counter_len))
+        } else {
+            None
+        }
+    }
+}
+
+/// The reasons a CTR mode cipher can not be created
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CtrError {
+    /// The layout does not fill a cipher block
+    LayoutLength {
+        /// The length of the layout
+        len: usize,
+        /// The block size of the cipher
+        block_size: usize,
+    },
+    /// The counter width is zero
+    CounterWidth {
+        /// The width of the counter
+        counter_len: usize,
+    },
+    /// The nonce is the wrong length for the layout
+    NonceLength {
+        /// The length of the nonce
+        len: usize,
+        /// The nonce length in the layout
+        expected: usize,
+    },
+    /// The initial counter is the wrong length for the layout
+    CounterLength {
+        /// The length of the initial counter
+        len: usize,
+        /// The counter width in the layout
+        expected: usize,
+    },
+}
+
+impl fmt::Display for CtrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CtrError::LayoutLength { len, block_size } => write!(
+                f,
+                "CTR layout length {} does not match the block size {}",
+                len, block_size
+            ),
+            CtrError::CounterWidth { counter_len } => write!(
+                f,
+                "CTR counter width {} must be at least 1 byte",
+                counter_len
+            ),
+            CtrError::NonceLength { len, expected } => write!(
+                f,
+                "CTR nonce length {} does not match the layout: expected {}",
+                len, expected
+            ),
+            CtrError::CounterLength { len, expected } => write!(
+                f,
+                "CTR initial counter length {} does not match the layout: expected {}",
+                len, expected
+            ),
+        }
+    }
+}
+
+/// A block cipher in counter mode, with a fixed nonce, and a stream position.
+#[derive(Clone)]
+pub struct Ctr<C> {
+    /// The block cipher
+    cipher: C,
+    /// The counter block layout
+    layout: CtrLayout,
+    /// The first counter block: the nonce and initial counter, followed by unused zero bytes
+    initial: [u8; MAX_BLOCK_BYTES],
+    /// The byte offset of the next apply_keystream() call
+    position: u64,
+}
+
+impl<C: BlockCipher> Ctr<C> {
+    /// Create a CTR mode cipher, using cipher, with counter blocks in layout, starting with
+    /// nonce and a zero counter. The stream position starts at zero.
+    ///
+    /// Returns an error if the layout does not match the cipher block size, the counter width is
+    /// invalid, or nonce is the wrong length.
+    pub fn new(cipher: C, layout: CtrLayout, nonce: &[u8]) -> Result<Self, CtrError> {
+        let zero = [0; MAX_BLOCK_BYTES];
+        let counter_len = layout.counter_len.min(MAX_BLOCK_BYTES);
+        Self::with_counter(cipher, layout, nonce, &zero[..counter_len])
+    }
+
+    /// Create a CTR mode cipher, using cipher, with counter blocks in layout, starting with
+    /// nonce and the initial counter, in the layout's byte order. The stream position starts at
+    /// zero.
+    ///
+    /// Returns an error if the layout does not match the cipher block size, the counter width is
+    /// invalid, or nonce or counter is the wrong length.
+    pub fn with_counter(
+        cipher: C,
+        layout: CtrLayout,
+        nonce: &[u8],
+        counter: &[u8],
+    ) -> Result<Self, CtrError> {
+        let block_size = cipher.block_size();
+        if layout.block_size() != block_size || block_size > MAX_BLOCK_BYTES {
+            return Err(CtrError::LayoutLength {
+                len: layout.block_size(),
+                block_size,
+            });
+        }
+        if layout.counter_len == 0 {
+            return Err(CtrError::CounterWidth {
+                counter_len: layout.counter_len,
+            });
+        }
+        if nonce.len() != layout.nonce_len {
+            return Err(CtrError::NonceLength {
+                len: nonce.len(),
+                expected: layout.nonce_len,
+            });
+        }
+
+        if counter.len() != layout.counter_len {
+            return Err(CtrError::CounterLength {
+                len: counter.len(),
+                expected: layout.counter_len,
+            });
+        }
+
+        let mut initial = [0; MAX_BLOCK_BYTES];
+        initial[..nonce.len()].copy_from_slice(nonce);
+        initial[nonce.len()..block_size].copy_from_slice(counter);
+        Ok(Ctr {
+            cipher,
+            layout,
+            initial,
+            position: 0,
+        })
+    }
+
+    /// Returns the counter block layout.
+    pub fn layout(&self) -> CtrLayout {
+        self.layout
+    }
+
+    /// Returns the byte offset of the next apply_keystream() call.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Set the byte offset of the next apply_keystream() call.
+    pub fn seek(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Fill block with the counter block for block_index: the nonce, followed by the initial
+    /// counter plus block_index. The counter wraps within its width, without changing the nonce.
+    ///
+    /// Panics if block_index does not fit in the counter, or block is not one block long.
+    pub fn counter_block(&self, block_index: u64, block: &mut [u8]) {
+        let CtrLayout {
+            nonce_len,
+            counter_len,
+            counter_endian,
+        } = self.layout;
+        assert_eq!(
+            block.len(),
+            self.layout.block_size(),
+            "CTR blocks must be one block long"
+        );
+        if let Some(max_blocks) = self.layout.max_blocks() {
+            assert!(
+                block_index < max_blocks,
+                "CTR block index must fit in the {} byte counter",
+                counter_len
+            );
+        }
+
+        block.copy_from_slice(&self.initial[..block.len()]);
+
+        // Add block_index to the counter, from its least significant byte
+        let mut carry = u128::from(block_index);
+        let mut add = |b: &mut u8| {
+            carry += u128::from(*b);
+            *b = carry as u8;
+            carry >>= 8;
+        };
+        let counter = &mut block[nonce_len..];
+        match counter_endian {
+            Endian::Big => counter.iter_mut().rev().for_each(&mut add),
+            Endian::Little => counter.iter_mut().for_each(&mut add),
+        }
+    }
+
+    /// XOR data with the keystream starting at byte offset, so encryption and decryption are the
+    /// same operation. Does not allocate, or change the stream position.
+    ///
+    /// Panics if the keystream would need a block index which does not fit in the counter.
+    pub fn apply_keystream_at(&self, offset: u64, data: &mut [u8]) {
+        let block_size = self.layout.block_size();
+        let mut block_buf = [0; MAX_BLOCK_BYTES];
+        let block = &mut block_buf[..block_size];
+
+        let mut block_index = offset / block_size as u64;
+        let mut skip = (offset % block_size as u64) as usize;
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            self.counter_block(block_index, block);
+            self.cipher.encrypt_block(block);
+
+            let len = remaining.len().min(block_size - skip);
+            let (chunk, rest) = remaining.split_at_mut(len);
+            for (d, k) in chunk.iter_mut().zip(&block[skip..]) {
+                *d ^= k;
+            }
+
+            remaining = rest;
+            block_index += 1;
+            skip = 0;
+        }
+    }
+
+    /// XOR data with the keystream at the stream position, then advance the position past it.
+    /// Does not allocate.
+    ///
+    /// Panics if the keystream would need a block index which does not fit in the counter.
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        self.apply_keystream_at(self.position, data);
+        self.position += data.len() as u64;
+    }
+
+    /// Returns len bytes of keystream, starting at byte offset.
+    ///
+    /// Panics if the keystream would need a block index which does not fit in the counter.
+    #[cfg(feature = "alloc")]
+    pub fn keystream(&self, offset: u64, len: usize) -> Vec<u8> {
+        let mut keystream = vec![0; len];
+        self.apply_keystream_at(offset, &mut keystream);
+        keystream
+    }
+}
+
+impl<C> fmt::Debug for Ctr<C> {
+    /// Formats the layout and position, without the cipher, nonce, or initial counter.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ctr")
+            .field("layout", &self.layout)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    use crate::aes::{Aes, AES_BLOCK_BYTES};
+    use crate::cipher::{self, Mode};
+
+    /// Returns the AES-128 cipher for the challenge key.
+    fn aes() -> Aes {
+        Aes::new(b"YELLOW SUBMARINE").expect("key is valid")
+    }
+
+    #[test]
+    fn counter_blocks() {
+        let nonce = [0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7];
+        let mut block = [0; AES_BLOCK_BYTES];
+
+        let ctr = Ctr::new(aes(), CtrLayout::NONCE_64_COUNTER_64_LE, &nonce).expect("valid");
+        ctr.counter_block(0x0102, &mut block);
+        assert_eq!(block[..8], nonce);
+        assert_eq!(block[8..], [2, 1, 0, 0, 0, 0, 0, 0]);
+
+        let ctr = Ctr::new(aes(), CtrLayout::NONCE_96_COUNTER_32_BE, &[7; 12]).expect("valid");
+        ctr.counter_block(0x0102, &mut block);
+        assert_eq!(block[..12], [7; 12]);
+        assert_eq!(block[12..], [0, 0, 1, 2]);
+        assert_eq!(ctr.layout().max_blocks(), Some(1 << 32));
+    }
+
+    #[test]
+    fn initial_counter() {
+        let mut block = [0; AES_BLOCK_BYTES];
+
+        // The counter carries, and wraps without changing the nonce
+        let layout = CtrLayout::NONCE_96_COUNTER_32_BE;
+        let ctr = Ctr::with_counter(aes(), layout, &[7; 12], &[0, 0, 0xff, 0xfe]).expect("valid");
+        ctr.counter_block(2, &mut block);
+        assert_eq!(block[..12], [7; 12]);
+        assert_eq!(block[12..], [0, 1, 0, 0]);
+        let ctr = Ctr::with_counter(aes(), layout, &[7; 12], &[0xff; 4]).expect("valid");
+        ctr.counter_block(2, &mut block);
+        assert_eq!(block[..12], [7; 12]);
+        assert_eq!(block[12..], [0, 0, 0, 1]);
+
+        let layout = CtrLayout::NONCE_64_COUNTER_64_LE;
+        let ctr = Ctr::with_counter(aes(), layout, &[7; 8], &[0xff, 0xff, 0, 0, 0, 0, 0, 0])
+            .expect("valid");
+        ctr.counter_block(1, &mut block);
+        assert_eq!(block[8..], [0, 0, 1, 0, 0, 0, 0, 0]);
+
+        // A full block counter matches the generic mode, with the counter in the IV
+        let layout = CtrLayout::full_block_be(AES_BLOCK_BYTES);
+        let iv = [0xf0; AES_BLOCK_BYTES];
+        let ctr = Ctr::with_counter(aes(), layout, &[], &iv).expect("valid");
+        assert_eq!(ctr.layout().max_blocks(), None);
+        let wrapped =
+            Ctr::with_counter(aes(), layout, &[], &[0xff; AES_BLOCK_BYTES]).expect("valid");
+        wrapped.counter_block(1, &mut block);
+        assert_eq!(block, [0; AES_BLOCK_BYTES]);
+        let plaintext: Vec<u8> = (0..100).collect();
+        let mut ciphertext = plaintext.clone();
+        ctr.apply_keystream_at(0, &mut ciphertext);
+        assert_eq!(
+            cipher::encrypt(&aes(), Mode::Ctr, &iv, &plaintext),
+            Ok(ciphertext)
+        );
+    }
+
+    #[test]
+    fn random_access() {
+        let mut ctr = Ctr::new(aes(), CtrLayout::NONCE_64_COUNTER_64_LE, &[9; 8]).expect("valid");
+        let keystream = ctr.keystream(0, 100);
+        for offset in [0, 1, 15, 16, 17, 50] {
+            for len in [0, 1, 16, 33] {
+                assert_eq!(
+                    ctr.keystream(offset as u64, len),
+                    keystream[offset..][..len]
+                );
+            }
+        }
+
+        // Streaming in pieces matches a single call
+        let mut data = vec![0; 100];
+        for piece in data.chunks_mut(7) {
+            ctr.apply_keystream(piece);
+        }
+        assert_eq!(data, keystream);
+        assert_eq!(ctr.position(), 100);
+
+        // Seeking back decrypts in place
+        ctr.seek(30);
+        ctr.apply_keystream(&mut data[30..40]);
+        assert_eq!(data[30..40], [0; 10]);
+        assert_eq!(
+            format!("{:?}", ctr),
+            "Ctr { layout: CtrLayout { nonce_len: 8, counter_len: 8, counter_endian: Little }, position: 40, .. }"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let layout = CtrLayout::NONCE_64_COUNTER_64_LE;
+        assert_eq!(
+            Ctr::new(aes(), layout, &[0; 12]).map(|c| c.layout()),
+            Err(CtrError::NonceLength {
+                len: 12,
+                expected: 8
+            })
+        );
+        let short = CtrLayout {
+            nonce_len: 4,
+            ..layout
+        };
+        assert_eq!(
+            Ctr::new(aes(), short, &[0; 4]).map(|c| c.layout()),
+            Err(CtrError::LayoutLength {
+                len: 12,
+                block_size: AES_BLOCK_BYTES
+            })
+        );
+        let no_counter = CtrLayout {
+            nonce_len: 16,
+            counter_len: 0,
+            counter_endian: Endian::Big,
+        };
+        assert_eq!(
+            Ctr::new(aes(), no_counter, &[0; 16]).map(|c| c.layout()),
+            Err(CtrError::CounterWidth { counter_len: 0 })
+        );
+        assert_eq!(
+            Ctr::with_counter(aes(), layout, &[0; 8], &[0; 4]).map(|c| c.layout()),
+            Err(CtrError::CounterLength {
+                len: 4,
+                expected: 8
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CTR block index must fit in the 1 byte counter")]
+    fn invalid_counter_overflow() {
+        let layout = CtrLayout {
+            nonce_len: 15,
+            counter_len: 1,
+            counter_endian: Endian::Big,
+        };
+        let ctr = Ctr::new(aes(), layout, &[0; 15]).expect("valid");
+        ctr.keystream(255 * AES_BLOCK_BYTES as u64, AES_BLOCK_BYTES + 1);
+    }
+}
@@ -0,0 +1,35 @@
+//! Challenge data files, Matasano Cryptopals Challenges
+//!
+//! Data files are downloaded from the challenge website, and saved in the data directory, using
+//! their original names. Challenges fail if their data files are missing.
+
+#![deny(missing_docs)]
+
+use std::fs;
+use std::path::PathBuf;
+
+/// The directory containing the challenge data files, relative to the crate root
+const DATA_DIR: &str = "data";
+
+/// The URL of the directory containing the original challenge data files
+const DATA_URL: &str = "https://cryptopals.com/static/challenge-data";
+
+/// Returns the contents of the challenge data file name.
+///
+/// Panics if the file can not be read, so a missing file fails its challenge checks.
+pub fn read_data_file(name: &str) -> String {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), DATA_DIR, name]
+        .iter()
+        .collect();
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => panic!(
+            "Reading data file '{}' failed: {}. Download it from {}/{}",
+            path.display(),
+            e,
+            DATA_URL,
+            name
+        ),
+    }
+}
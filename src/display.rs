@@ -0,0 +1,50 @@
+//! Allocation-free formatting helpers
+
+#![deny(missing_docs)]
+
+use core::fmt::{self, Write};
+
+/// The marker written after truncated output
+pub const ELLIPSIS: &str = "...";
+
+/// Write an encoded string of char_count characters into f, without allocating.
+///
+/// encode(f, n) must write exactly the first n characters of the encoded string into f.
+///
+/// Honours the width, fill, alignment and precision of f:
+///  * the precision is the maximum number of characters written (excluding any fill). If the
+///    encoded string is longer, it is truncated, and the final characters are replaced by ELLIPSIS.
+///  * the output is left-aligned by default, like other strings.
+pub fn fmt_encoded<F>(f: &mut fmt::Formatter, char_count: usize, encode: F) -> fmt::Result
+where
+    F: FnOnce(&mut fmt::Formatter, usize) -> fmt::Result,
+{
+    // If there isn't enough room for the whole ellipsis, write as much of it as we can
+    let (shown, ellipsis) = match f.precision() {
+        Some(max) if max < char_count => {
+            let ellipsis_len = ELLIPSIS.len().min(max);
+            (max - ellipsis_len, &ELLIPSIS[..ellipsis_len])
+        }
+        _ => (char_count, ""),
+    };
+
+    let len = shown + ellipsis.len();
+    let padding = f.width().map_or(0, |width| width.saturating_sub(len));
+    let (pre_pad, post_pad) = match f.align() {
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(fmt::Alignment::Left) | None => (0, padding),
+    };
+
+    let fill = f.fill();
+    for _ in 0..pre_pad {
+        f.write_char(fill)?;
+    }
+    encode(f, shown)?;
+    f.write_str(ellipsis)?;
+    for _ in 0..post_pad {
+        f.write_char(fill)?;
+    }
+
+    Ok(())
+}
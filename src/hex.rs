@@ -2,12 +2,14 @@
 
 #![deny(missing_docs)]
 
-// I don't want to create a duplicate module here. But I can't get "crate" to work.
-#[path = "math.rs"]
-#[allow(dead_code)]
-mod math;
+use core::fmt;
+use core::str;
 
-use math::BYTE_BITS;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::display;
+use crate::math::{self, BYTE_BITS};
 
 /// The number of bits in a hex digit
 const HEX_CHAR_BITS: usize = 4;
@@ -21,6 +23,8 @@ const HEX_BLOCK_BYTES: usize = 1;
 const HEX_BLOCK_CHARS: usize = 2;
 /// The number of bits in a hex conversion block
 const HEX_BLOCK_BITS: usize = HEX_BLOCK_BYTES * BYTE_BITS;
+// The length calculations rely on each block being a whole number of bytes and characters
+const _: () = assert!(HEX_BLOCK_BITS == HEX_BLOCK_CHARS * HEX_CHAR_BITS);
 
 /// Encode char_bits into a hex character.
 /// Panics if char_bits is greater than HEX_MAX.
@@ -32,30 +36,93 @@ fn hex_encode_char(char_bits: u8) -> char {
     }
 }
 
-/// Encode a single-byte block, into a HEX_BLOCK_CHARS character hex string.
-fn hex_encode_block(block: u8) -> String {
-    let mut s = String::with_capacity(HEX_BLOCK_CHARS);
+/// Encode a single-byte block, into HEX_BLOCK_CHARS hex characters.
+fn hex_encode_block_chars(block: u8) -> [char; HEX_BLOCK_CHARS] {
+    [
+        hex_encode_char((block & 0b11110000) >> 4),
+        hex_encode_char(block & 0b00001111),
+    ]
+}
 
-    s.push(hex_encode_char((block & 0b11110000) >> 4));
-    s.push(hex_encode_char(block & 0b00001111));
+/// Returns the number of hex characters needed to encode byte_count bytes.
+pub fn hex_encode_len(byte_count: usize) -> usize {
+    // Each 8 bit block turns 1 byte into 2 hex characters
+    let hex_blocks = byte_count;
+    hex_blocks * HEX_BLOCK_CHARS
+}
 
-    assert!(s.len() == HEX_BLOCK_CHARS);
-    s
+/// Encode bytes into hex, writing the hex characters to the start of out.
+/// Returns the hex string, borrowed from out. Does not allocate.
+///
+/// Panics if out is shorter than hex_encode_len(bytes.len()).
+pub fn hex_encode_to_slice<'a>(bytes: &[u8], out: &'a mut [u8]) -> &'a str {
+    let char_count = hex_encode_len(bytes.len());
+    assert!(
+        out.len() >= char_count,
+        "Hex output buffer is too short: {} < {}",
+        out.len(),
+        char_count
+    );
+    let out = &mut out[..char_count];
+
+    // Each byte is a block
+    for (block, out_block) in bytes.iter().zip(out.chunks_mut(HEX_BLOCK_CHARS)) {
+        let chars = hex_encode_block_chars(*block);
+        for (c, out_c) in chars.iter().zip(out_block.iter_mut()) {
+            // Hex characters are ASCII, so each character is one byte
+            *out_c = *c as u8;
+        }
+    }
+
+    str::from_utf8(out).expect("Hex characters are ASCII")
 }
 
 /// Encode bytes into a hex string.
+#[cfg(feature = "alloc")]
 pub fn hex_encode(bytes: &[u8]) -> String {
-    // Each 8 bit block turns 1 byte into 2 hex characters
-    let hex_blocks = bytes.len();
-    let char_count = hex_blocks * HEX_BLOCK_CHARS;
+    let char_count = hex_encode_len(bytes.len());
 
-    // Each byte is a block
-    let s: String = bytes.iter().map(|b| hex_encode_block(*b)).collect();
+    let mut v = vec![0; char_count];
+    hex_encode_to_slice(bytes, &mut v);
+
+    let s = String::from_utf8(v).expect("Hex characters are ASCII");
     assert!(s.len() == char_count);
 
     s
 }
 
+/// A lazy hex encoder for bytes, which formats directly into a fmt::Formatter.
+///
+/// Unlike hex_encode(), displaying a HexDisplay does not allocate.
+/// Honours width, fill and alignment. The precision is the maximum number of characters to
+/// display: longer hex strings are truncated, and end with display::ELLIPSIS.
+#[derive(Clone, Copy, Debug)]
+pub struct HexDisplay<'a> {
+    /// The bytes to encode
+    bytes: &'a [u8],
+}
+
+impl<'a> HexDisplay<'a> {
+    /// Create a hex display adapter for bytes.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        HexDisplay { bytes }
+    }
+}
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let char_count = self.bytes.len() * HEX_BLOCK_CHARS;
+
+        display::fmt_encoded(f, char_count, |f, shown| {
+            let chars = self.bytes.iter().flat_map(|b| hex_encode_block_chars(*b));
+            for c in chars.take(shown) {
+                fmt::Write::write_char(f, c)?;
+            }
+            Ok(())
+        })
+    }
+}
+
 /// Decode a hex character c into its corresponding HEX_CHAR_BITS bits.
 /// Panics on non-hex characters, including (partial) multibyte characters.
 fn hex_decode_char(c: char) -> u8 {
@@ -85,10 +152,50 @@ fn hex_decode_block(block: &[u8]) -> u8 {
     // The caller should ensure that these are ASCII
     assert!(block.iter().all(|b| (*b as char).is_ascii()));
 
-    let c: Vec<u8> = block.iter().map(|b| hex_decode_char(*b as char)).collect();
-    assert!(c.len() == HEX_BLOCK_CHARS);
+    let c0 = hex_decode_char(block[0] as char);
+    let c1 = hex_decode_char(block[1] as char);
+
+    (c0 & 0b1111) << 4 | c1 & 0b1111
+}
 
-    (c[0] & 0b1111) << 4 | c[1] & 0b1111
+/// Returns the number of bytes decoded from char_count hex characters.
+///
+/// Panics if char_count is not a multiple of HEX_BLOCK_CHARS.
+pub fn hex_decode_len(char_count: usize) -> usize {
+    // Each 8 bit block turns 2 hex characters into 1 byte
+    // This division must be exact.
+    // We might want to change this condition in future, to allow trailing hex nybbles.
+    // Dividing by characters rather than bits avoids overflow for large character counts
+    math::exact_div(char_count, HEX_BLOCK_CHARS)
+}
+
+/// Decode a hex string s, writing the bytes to the start of out.
+/// Returns the decoded bytes, borrowed from out. Does not allocate.
+///
+/// Panics if:
+///  * s is not a multiple of HEX_BLOCK_CHARS long,
+///  * s contains non-hex characters, including multibyte characters, or
+///  * out is shorter than hex_decode_len(s.len()).
+pub fn hex_decode_to_slice<'a>(s: &str, out: &'a mut [u8]) -> &'a [u8] {
+    // Hex strings must be ASCII
+    assert!(s.is_ascii(), "Invalid hex string");
+
+    let byte_count = hex_decode_len(s.len());
+    assert!(
+        out.len() >= byte_count,
+        "Hex output buffer is too short: {} < {}",
+        out.len(),
+        byte_count
+    );
+    let out = &mut out[..byte_count];
+
+    // Since the string is ASCII, we can safely iterate over (chunks of) its bytes.
+    let blocks = s.as_bytes().chunks(HEX_BLOCK_CHARS);
+    for (block, out_b) in blocks.zip(out.iter_mut()) {
+        *out_b = hex_decode_block(block);
+    }
+
+    out
 }
 
 /// Decode a hex string s into bytes.
@@ -96,27 +203,54 @@ fn hex_decode_block(block: &[u8]) -> u8 {
 /// Panics if:
 ///  * s is not a multiple of HEX_BLOCK_CHARS long,
 ///  * s contains non-hex characters, including multibyte characters.
+#[cfg(feature = "alloc")]
 pub fn hex_decode(s: &str) -> Vec<u8> {
     // Hex strings must be ASCII
     assert!(s.is_ascii(), "Invalid hex string");
 
-    // Each 8 bit block turns 2 hex characters into 1 byte
-    // This division must be exact.
-    // We might want to change this condition in future, to allow trailing hex nybbles.
-    let byte_count = math::exact_div(s.len() * HEX_CHAR_BITS, HEX_BLOCK_BITS);
+    let byte_count = hex_decode_len(s.len());
 
-    // Since the string is ASCII, we can safely iterate over (chunks of) its bytes.
-    let v: Vec<u8> = s
-        .as_bytes()
-        .chunks(HEX_BLOCK_CHARS)
-        .map(|block| hex_decode_block(block))
-        .collect();
+    let mut v = vec![0; byte_count];
+    hex_decode_to_slice(s, &mut v);
 
     assert!(v.len() == byte_count);
     v
 }
 
 #[cfg(test)]
+mod slice_tests {
+    use super::*;
+
+    #[test]
+    fn slices() {
+        let mut out = [0; 6];
+        assert_eq!(hex_encode_to_slice(&[0, 9, 255], &mut out), "0009ff");
+        assert_eq!(hex_encode_to_slice(&[16], &mut out), "10");
+        assert_eq!(hex_encode_to_slice(&[], &mut out), "");
+
+        let mut out = [0; 3];
+        assert_eq!(hex_decode_to_slice("0009ff", &mut out), [0, 9, 255]);
+        assert_eq!(hex_decode_to_slice("A0", &mut out), [160]);
+        assert_eq!(hex_decode_to_slice("", &mut out), []);
+
+        assert_eq!(hex_encode_len(3), 6);
+        assert_eq!(hex_decode_len(6), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Hex output buffer is too short")]
+    fn invalid_hex_encode_slice_too_short() {
+        hex_encode_to_slice(&[0, 9, 255], &mut [0; 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Hex output buffer is too short")]
+    fn invalid_hex_decode_slice_too_short() {
+        hex_decode_to_slice("0009ff", &mut [0; 2]);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
@@ -172,6 +306,40 @@ mod tests {
         assert_eq!(hex_encode(&hex_decode(test_vector)), test_vector);
     }
 
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", HexDisplay::new(&[])), "");
+        assert_eq!(format!("{}", HexDisplay::new(&[0, 9, 255])), "0009ff");
+        assert_eq!(
+            format!("{}", HexDisplay::new(&[16, 2, 0xee, 0xaf])),
+            hex_encode(&[16, 2, 0xee, 0xaf])
+        );
+    }
+
+    #[test]
+    fn display_width() {
+        assert_eq!(format!("{:6}", HexDisplay::new(&[0xab])), "ab    ");
+        assert_eq!(format!("{:<6}", HexDisplay::new(&[0xab])), "ab    ");
+        assert_eq!(format!("{:>6}", HexDisplay::new(&[0xab])), "    ab");
+        assert_eq!(format!("{:^6}", HexDisplay::new(&[0xab])), "  ab  ");
+        assert_eq!(format!("{:*^7}", HexDisplay::new(&[0xab])), "**ab***");
+        // Width is a minimum
+        assert_eq!(format!("{:1}", HexDisplay::new(&[0xab, 0xcd])), "abcd");
+    }
+
+    #[test]
+    fn display_precision() {
+        let bytes = [0x01, 0x23, 0x45, 0x67, 0x89];
+        assert_eq!(format!("{:.10}", HexDisplay::new(&bytes)), "0123456789");
+        assert_eq!(format!("{:.20}", HexDisplay::new(&bytes)), "0123456789");
+        assert_eq!(format!("{:.9}", HexDisplay::new(&bytes)), "012345...");
+        assert_eq!(format!("{:.4}", HexDisplay::new(&bytes)), "0...");
+        assert_eq!(format!("{:.3}", HexDisplay::new(&bytes)), "...");
+        assert_eq!(format!("{:.1}", HexDisplay::new(&bytes)), ".");
+        assert_eq!(format!("{:.0}", HexDisplay::new(&bytes)), "");
+        assert_eq!(format!("{:>8.5}", HexDisplay::new(&bytes)), "   01...");
+    }
+
     #[test]
     #[should_panic(expected = "Invalid hex character")]
     fn invalid_hex_char_before_0() {
@@ -0,0 +1,39 @@
+//! Matasano Cryptopals Challenges, shared modules
+//!
+//! These modules build under no_std. The alloc feature enables the String and Vec based APIs.
+//! Without it, only the slice-based APIs are available, and no allocator is needed.
+//! The std feature enables the modules that need floating-point maths or OS APIs.
+
+#![no_std]
+#![deny(missing_docs)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(test, feature = "std"))]
+#[cfg_attr(test, macro_use)]
+extern crate std;
+
+pub mod aes;
+#[cfg(feature = "alloc")]
+pub mod attack;
+pub mod base64;
+#[cfg(feature = "alloc")]
+pub mod cbc;
+pub mod cipher;
+#[cfg(feature = "alloc")]
+pub mod cookie;
+pub mod ctr;
+pub mod display;
+pub mod hex;
+pub mod math;
+#[cfg(feature = "alloc")]
+pub mod oracle;
+pub mod padding;
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod score;
+#[cfg(all(test, feature = "alloc"))]
+mod test_util;
+pub mod utf8;
+pub mod xor;
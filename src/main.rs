@@ -4,10 +4,64 @@
 
 // Each challenge is a sub-module of the binary root module
 
+/// Challenge data files
+mod data;
+
 /// Set 1, Challenge 1
 mod s1c1;
+/// Set 1, Challenge 2
+mod s1c2;
+/// Set 1, Challenge 3
+mod s1c3;
+/// Set 1, Challenge 4
+mod s1c4;
+/// Set 1, Challenge 5
+mod s1c5;
+/// Set 1, Challenge 6
+mod s1c6;
+/// Set 1, Challenge 7
+mod s1c7;
+/// Set 1, Challenge 8
+mod s1c8;
+/// Set 2, Challenge 10
+mod s2c10;
+/// Set 2, Challenge 11
+mod s2c11;
+/// Set 2, Challenge 12
+mod s2c12;
+/// Set 2, Challenge 13
+mod s2c13;
+/// Set 2, Challenge 14
+mod s2c14;
+/// Set 2, Challenge 15
+mod s2c15;
+/// Set 2, Challenge 16
+mod s2c16;
+/// Set 2, Challenge 9
+mod s2c9;
+/// Set 3, Challenge 17
+mod s3c17;
+/// Set 3, Challenge 18
+mod s3c18;
 
 /// Check the results of each challenge
 fn main() {
     s1c1::check();
+    s1c2::check();
+    s1c3::check();
+    s1c4::check();
+    s1c5::check();
+    s1c6::check();
+    s1c7::check();
+    s1c8::check();
+    s2c9::check();
+    s2c10::check();
+    s2c11::check();
+    s2c12::check();
+    s2c13::check();
+    s2c14::check();
+    s2c15::check();
+    s2c16::check();
+    s3c17::check();
+    s3c18::check();
 }
@@ -2,41 +2,149 @@
 
 #![deny(missing_docs)]
 
+use core::fmt;
+use core::ops::{Add, Div, Rem};
+
+#[cfg(feature = "alloc")]
+mod biguint;
+#[cfg(feature = "alloc")]
+mod modular;
+#[cfg(feature = "alloc")]
+mod montgomery;
+#[cfg(feature = "alloc")]
+mod prime;
+
+#[cfg(feature = "alloc")]
+pub use biguint::BigUint;
+#[cfg(feature = "alloc")]
+pub use modular::{crt, ExtendedGcd, ModularError};
+#[cfg(feature = "alloc")]
+pub use montgomery::Montgomery;
+#[cfg(feature = "alloc")]
+pub use prime::MILLER_RABIN_ROUNDS;
+
+/// The number of bits in a byte
 pub const BYTE_BITS: usize = 8;
 
-/// Divide n by d, returning (n/d + 1) if there is any remainder
-/// Panics on:
+/// An unsigned primitive integer type, which can be used with the generic division functions.
+pub trait Unsigned:
+    Copy
+    + Eq
+    + Ord
+    + fmt::Debug
+    + fmt::Display
+    + Add<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    /// The value 0
+    const ZERO: Self;
+    /// The value 1
+    const ONE: Self;
+    /// The maximum value of this type
+    const MAX: Self;
+}
+
+/// Implement Unsigned for each of the listed unsigned primitive integer types
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Unsigned for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$t>::MAX;
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+/// The reasons an exact division can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DivError<T: Unsigned> {
+    /// The divisor was zero
+    ZeroDivisor,
+    /// The division had a non-zero remainder
+    Inexact {
+        /// The numerator
+        n: T,
+        /// The divisor
+        d: T,
+        /// The non-zero remainder n % d
+        remainder: T,
+    },
+}
+
+impl<T: Unsigned> fmt::Display for DivError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DivError::ZeroDivisor => write!(f, "The divisor must not be zero"),
+            DivError::Inexact { n, d, remainder } => write!(
+                f,
+                "Expected exact division, but {} / {} has remainder {}",
+                n, d, remainder
+            ),
+        }
+    }
+}
+
+/// Divide n by d, returning (n/d + 1) if there is any remainder.
+/// Returns None if the divisor is zero: d == 0.
+///
+/// Never overflows, because the result is always less than or equal to n.
+pub fn checked_ceil_div<T: Unsigned>(n: T, d: T) -> Option<T> {
+    if d == T::ZERO {
+        return None;
+    }
+
+    // If there is a remainder, d > 1, so n / d < n <= T::MAX
+    if n % d == T::ZERO {
+        Some(n / d)
+    } else {
+        Some(n / d + T::ONE)
+    }
+}
+
+/// Divide n by d, ensuring that the division is exact
+/// Returns an error on:
 ///  * zero divisor: d == 0, or
-///  * internal overflow: n + d > usize::MAX.
-pub fn ceil_div(n: usize, d: usize) -> usize {
-    assert!(d > 0, "The divisor must not be zero");
-    assert!(
-        // n + d <= usize::MAX, but without overflow
-        n <= usize::MAX - d,
-        "The sum of the numerator {} and divisor {} must be less than or equal to usize::MAX ({})",
-        n,
-        d,
-        usize::MAX
-    );
+///  * non-zero remainder: n % d != 0.
+pub fn checked_exact_div<T: Unsigned>(n: T, d: T) -> Result<T, DivError<T>> {
+    if d == T::ZERO {
+        return Err(DivError::ZeroDivisor);
+    }
+
+    let remainder = n % d;
+    if remainder != T::ZERO {
+        return Err(DivError::Inexact { n, d, remainder });
+    }
 
-    (n + d - 1) / d
+    Ok(n / d)
+}
+
+/// Divide n by d, returning (n/d + 1) if there is any remainder
+/// Panics on zero divisor: d == 0.
+///
+/// See checked_ceil_div() for a non-panicking version, which supports all unsigned types.
+pub fn ceil_div(n: usize, d: usize) -> usize {
+    match checked_ceil_div(n, d) {
+        Some(q) => q,
+        None => panic!("{}", DivError::<usize>::ZeroDivisor),
+    }
 }
 
 /// Divide n by d, ensuring that the division is exact
 /// Panics on:
 ///  * zero divisor: d == 0, or
 ///  * non-zero remainder: n % d != 0.
+///
+/// See checked_exact_div() for a non-panicking version, which supports all unsigned types.
 pub fn exact_div(n: usize, d: usize) -> usize {
-    assert!(d > 0, "The divisor must not be zero");
-    assert!(
-        n % d == 0,
-        "Expected exact division, but {} / {} has remainder {}",
-        n,
-        d,
-        n % d
-    );
-
-    n / d
+    match checked_exact_div(n, d) {
+        Ok(q) => q,
+        Err(e) => panic!("{}", e),
+    }
 }
 
 /// Advance c by n characters, and return the result
@@ -66,6 +174,20 @@ pub fn char_diff(c: char, base_char: char) -> u8 {
     (c as u8) - (base_char as u8)
 }
 
+/// Returns the number of differing bits between left and right
+/// Panics if left and right are different lengths.
+pub fn hamming_distance(left: &[u8], right: &[u8]) -> usize {
+    assert!(
+        left.len() == right.len(),
+        "Hamming distance inputs must be the same length"
+    );
+
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| (l ^ r).count_ones() as usize)
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,6 +265,87 @@ mod tests {
         assert_eq!(exact_div(ceil_half_max, ceil_half_max), 1);
     }
 
+    #[test]
+    fn div_ceil_near_max() {
+        // These inputs would overflow n + d - 1
+        let floor_half_max = (usize::MAX - 1) / 2;
+        let ceil_half_max = (usize::MAX - 1) / 2 + 1;
+
+        assert_eq!(ceil_div(1, usize::MAX), 1);
+        assert_eq!(ceil_div(usize::MAX, 1), usize::MAX);
+        assert_eq!(ceil_div(usize::MAX, usize::MAX), 1);
+        assert_eq!(ceil_div(usize::MAX - 1, usize::MAX - 1), 1);
+        assert_eq!(ceil_div(usize::MAX, 2), ceil_half_max);
+        assert_eq!(ceil_div(usize::MAX - 1, 2), floor_half_max);
+        assert_eq!(ceil_div(ceil_half_max, ceil_half_max), 1);
+        assert_eq!(ceil_div(usize::MAX, usize::MAX - 1), 2);
+    }
+
+    #[test]
+    fn div_generic() {
+        assert_eq!(checked_ceil_div(255u8, 2), Some(128));
+        assert_eq!(checked_ceil_div(u8::MAX, u8::MAX), Some(1));
+        assert_eq!(checked_exact_div(250u8, 5), Ok(50));
+
+        assert_eq!(checked_ceil_div(u16::MAX, 256), Some(256));
+        assert_eq!(checked_exact_div(u16::MAX, 257), Ok(255));
+
+        assert_eq!(checked_ceil_div(u32::MAX, 2), Some(1 << 31));
+        assert_eq!(checked_ceil_div(u64::MAX, 1 << 32), Some(1 << 32));
+
+        assert_eq!(checked_ceil_div(u128::MAX, 3), Some(u128::MAX / 3));
+        assert_eq!(checked_ceil_div(u128::MAX, 1 << 64), Some(1 << 64));
+        assert_eq!(checked_exact_div(u128::MAX, u128::MAX), Ok(1));
+    }
+
+    #[test]
+    fn div_checked() {
+        assert_eq!(checked_ceil_div(5usize, 3), Some(2));
+        assert_eq!(checked_ceil_div(0u8, 1), Some(0));
+        assert_eq!(checked_ceil_div(u128::MAX, 2), Some(u128::MAX / 2 + 1));
+        assert_eq!(checked_ceil_div(1u32, 0), None);
+        assert_eq!(checked_ceil_div(0u64, 0), None);
+
+        assert_eq!(checked_exact_div(6usize, 3), Ok(2));
+        assert_eq!(checked_exact_div(u16::MAX, 1), Ok(u16::MAX));
+        assert_eq!(checked_exact_div(1u8, 0), Err(DivError::ZeroDivisor));
+        assert_eq!(
+            checked_exact_div(5u64, 3),
+            Err(DivError::Inexact {
+                n: 5,
+                d: 3,
+                remainder: 2
+            })
+        );
+        assert_eq!(
+            checked_exact_div(u128::MAX, 2),
+            Err(DivError::Inexact {
+                n: u128::MAX,
+                d: 2,
+                remainder: 1
+            })
+        );
+    }
+
+    #[test]
+    fn div_error_display() {
+        assert_eq!(
+            format!("{}", DivError::<u8>::ZeroDivisor),
+            "The divisor must not be zero"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                DivError::Inexact {
+                    n: 5u8,
+                    d: 3,
+                    remainder: 2
+                }
+            ),
+            "Expected exact division, but 5 / 3 has remainder 2"
+        );
+    }
+
     #[test]
     fn round_trip_char() {
         let c = 'A';
@@ -174,49 +377,6 @@ mod tests {
         ceil_div(0, 0);
     }
 
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_1_max() {
-        ceil_div(1, usize::MAX);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_max_1() {
-        ceil_div(usize::MAX, 1);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_max_max() {
-        ceil_div(usize::MAX, usize::MAX);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_ceil_max_minus_one() {
-        ceil_div(usize::MAX - 1, usize::MAX - 1);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_ceil_max_div_two() {
-        ceil_div(usize::MAX, 2);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_ceil_max_minus_one_div_two() {
-        ceil_div(usize::MAX - 1, 2);
-    }
-
-    #[test]
-    #[should_panic(expected = "must be less than or equal to usize::MAX")]
-    fn invalid_div_ceil_overflow_ceil_half_max() {
-        let ceil_half_max = (usize::MAX - 1) / 2 + 1;
-        ceil_div(ceil_half_max, ceil_half_max);
-    }
-
     #[test]
     #[should_panic(expected = "Expected exact division")]
     fn invalid_div_inexact_max() {
@@ -276,4 +436,18 @@ mod tests {
     fn invalid_char_diff_c_less_than_base() {
         char_diff('a', 'z');
     }
+
+    #[test]
+    fn hamming() {
+        assert_eq!(hamming_distance(&[], &[]), 0);
+        assert_eq!(hamming_distance(&[0xff], &[0x00]), 8);
+        assert_eq!(hamming_distance(&[0b1010], &[0b0110]), 2);
+        assert_eq!(hamming_distance(b"this is a test", b"wokka wokka!!!"), 37);
+    }
+
+    #[test]
+    #[should_panic(expected = "Hamming distance inputs must be the same length")]
+    fn invalid_hamming_length_mismatch() {
+        hamming_distance(&[1, 2], &[1]);
+    }
 }
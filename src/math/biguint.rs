@@ -0,0 +1,882 @@
+//! Arbitrary-precision unsigned integers
+
+#![deny(missing_docs)]
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, BitAnd, BitOr, Div, Mul, Rem, Shl, Shr, Sub};
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::hex;
+use crate::math::BYTE_BITS;
+
+/// A single BigUint digit
+pub(super) type Limb = u32;
+/// A double-width BigUint digit, used for intermediate results
+pub(super) type DoubleLimb = u64;
+
+/// The number of bits in a limb
+pub(super) const LIMB_BITS: usize = 32;
+/// The number of bytes in a limb
+const LIMB_BYTES: usize = LIMB_BITS / BYTE_BITS;
+
+/// The minimum number of limbs in both operands, before multiplication uses Karatsuba.
+/// The modpow bench shows one level of Karatsuba breaking even with schoolbook at about 3072 bits.
+const KARATSUBA_THRESHOLD: usize = 96;
+
+/// The largest power of ten that fits in a limb
+const DEC_LIMB_BASE: Limb = 1_000_000_000;
+/// The number of decimal digits in DEC_LIMB_BASE
+const DEC_LIMB_DIGITS: usize = 9;
+
+/// An arbitrary-precision unsigned integer.
+///
+/// Stored as little-endian limbs, with no trailing zero limbs. Zero has no limbs.
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct BigUint {
+    /// The limbs, least significant first
+    pub(super) limbs: Vec<Limb>,
+}
+
+impl BigUint {
+    /// Returns zero.
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    /// Returns one.
+    pub fn one() -> Self {
+        BigUint::from(1u64)
+    }
+
+    /// Create a BigUint from little-endian limbs, removing any trailing zero limbs.
+    pub(super) fn from_limbs(limbs: Vec<Limb>) -> Self {
+        let mut n = BigUint { limbs };
+        n.normalize();
+        n
+    }
+
+    /// Remove trailing zero limbs.
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    /// Returns true if self is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns true if self is one.
+    pub fn is_one(&self) -> bool {
+        self.limbs == [1]
+    }
+
+    /// Returns true if self is even. Zero is even.
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l & 1 == 0)
+    }
+
+    /// Returns the number of significant bits in self. Zero has no significant bits.
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * LIMB_BITS - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Returns the value of bit i, where bit 0 is the least significant bit.
+    pub fn bit(&self, i: usize) -> bool {
+        match self.limbs.get(i / LIMB_BITS) {
+            Some(l) => (l >> (i % LIMB_BITS)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    /// Returns self as a u64, or None if it is too large.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0] as u64),
+            2 => Some((self.limbs[1] as u64) << LIMB_BITS | self.limbs[0] as u64),
+            _ => None,
+        }
+    }
+
+    /// Create a BigUint from big-endian bytes. Leading zero bytes are ignored.
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(LIMB_BYTES)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |limb, b| limb << BYTE_BITS | *b as Limb)
+            })
+            .collect();
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns the big-endian bytes of self, with no leading zero bytes.
+    /// Zero is encoded as a single zero byte.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        if self.is_zero() {
+            return vec![0];
+        }
+
+        let byte_count = self.bits().div_ceil(BYTE_BITS);
+        let mut v: Vec<u8> = self
+            .limbs
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect();
+        v.drain(..v.len() - byte_count);
+
+        v
+    }
+
+    /// Create a BigUint from a hex string. Leading zeroes are ignored, and the string may be an odd
+    /// number of characters long.
+    ///
+    /// Panics if s is empty, or contains non-hex characters.
+    pub fn from_hex(s: &str) -> Self {
+        assert!(!s.is_empty(), "Invalid hex number: empty string");
+
+        if s.len() % 2 == 1 {
+            BigUint::from_bytes_be(&hex::hex_decode(&format!("0{}", s)))
+        } else {
+            BigUint::from_bytes_be(&hex::hex_decode(s))
+        }
+    }
+
+    /// Returns the lowercase hex representation of self, with no leading zeroes.
+    /// Zero is encoded as "0".
+    pub fn to_hex(&self) -> String {
+        let s = hex::hex_encode(&self.to_bytes_be());
+        let trimmed = s.trim_start_matches('0');
+
+        if trimmed.is_empty() {
+            String::from("0")
+        } else {
+            String::from(trimmed)
+        }
+    }
+
+    /// Create a BigUint from a decimal string. Leading zeroes are ignored.
+    ///
+    /// Panics if s is empty, or contains non-decimal characters.
+    pub fn from_dec_str(s: &str) -> Self {
+        assert!(!s.is_empty(), "Invalid decimal number: empty string");
+        assert!(
+            s.bytes().all(|b| b.is_ascii_digit()),
+            "Invalid decimal character in {:?}",
+            s
+        );
+
+        // Process the most significant chunk first, so the remaining chunks are all full-length
+        let first_len = match s.len() % DEC_LIMB_DIGITS {
+            0 => DEC_LIMB_DIGITS,
+            n => n,
+        };
+        let (first, rest) = s.split_at(first_len);
+
+        let mut n = BigUint::zero();
+        for chunk in
+            core::iter::once(first.as_bytes()).chain(rest.as_bytes().chunks(DEC_LIMB_DIGITS))
+        {
+            let digits = chunk.iter().fold(0, |acc, b| acc * 10 + (b - b'0') as Limb);
+            let scale = (10 as Limb).pow(chunk.len() as u32);
+            n = n.mul_limb_add(scale, digits);
+        }
+
+        n
+    }
+
+    /// Returns the decimal representation of self. Zero is encoded as "0".
+    pub fn to_dec_string(&self) -> String {
+        if self.is_zero() {
+            return String::from("0");
+        }
+
+        // Split into base DEC_LIMB_BASE digits, least significant first
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.divmod_limb(DEC_LIMB_BASE);
+            chunks.push(r);
+            n = q;
+        }
+
+        let mut chunks = chunks.iter().rev();
+        let mut s = format!(
+            "{}",
+            chunks.next().expect("non-zero has at least one chunk")
+        );
+        for chunk in chunks {
+            s.push_str(&format!("{:0width$}", chunk, width = DEC_LIMB_DIGITS));
+        }
+
+        s
+    }
+
+    /// Returns self * m + a.
+    fn mul_limb_add(&self, m: Limb, a: Limb) -> Self {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = a as DoubleLimb;
+        for l in &self.limbs {
+            let t = *l as DoubleLimb * m as DoubleLimb + carry;
+            limbs.push(t as Limb);
+            carry = t >> LIMB_BITS;
+        }
+        limbs.push(carry as Limb);
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns (self / d, self % d), for a single-limb divisor d.
+    ///
+    /// Panics if d is zero.
+    pub(super) fn divmod_limb(&self, d: Limb) -> (Self, Limb) {
+        assert!(d != 0, "The divisor must not be zero");
+
+        let mut q = vec![0; self.limbs.len()];
+        let mut r: DoubleLimb = 0;
+        for (i, l) in self.limbs.iter().enumerate().rev() {
+            let t = r << LIMB_BITS | *l as DoubleLimb;
+            q[i] = (t / d as DoubleLimb) as Limb;
+            r = t % d as DoubleLimb;
+        }
+
+        (BigUint::from_limbs(q), r as Limb)
+    }
+
+    /// Returns self + other.
+    fn add_ref(&self, other: &Self) -> Self {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (&self.limbs, &other.limbs)
+        } else {
+            (&other.limbs, &self.limbs)
+        };
+
+        let mut limbs = Vec::with_capacity(long.len() + 1);
+        let mut carry = 0;
+        for (i, l) in long.iter().enumerate() {
+            let t = *l as DoubleLimb + *short.get(i).unwrap_or(&0) as DoubleLimb + carry;
+            limbs.push(t as Limb);
+            carry = t >> LIMB_BITS;
+        }
+        limbs.push(carry as Limb);
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns self - other, or None if other is greater than self.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+        for (i, l) in self.limbs.iter().enumerate() {
+            let (t, b1) = l.overflowing_sub(*other.limbs.get(i).unwrap_or(&0));
+            let (t, b2) = t.overflowing_sub(borrow);
+            limbs.push(t);
+            borrow = (b1 || b2) as Limb;
+        }
+        assert!(
+            borrow == 0,
+            "Subtraction underflow should have been checked"
+        );
+
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    /// Returns self - other.
+    /// Panics if other is greater than self.
+    fn sub_ref(&self, other: &Self) -> Self {
+        self.checked_sub(other)
+            .expect("BigUint subtraction underflow: other must be less than or equal to self")
+    }
+
+    /// Returns self * other.
+    fn mul_ref(&self, other: &Self) -> Self {
+        if self.limbs.len().min(other.limbs.len()) >= KARATSUBA_THRESHOLD {
+            self.mul_karatsuba(other)
+        } else {
+            self.mul_schoolbook(other)
+        }
+    }
+
+    /// Returns self * other, using Karatsuba multiplication.
+    /// Each level of recursion does 3 half-size multiplications, rather than 4. The half-size
+    /// multiplications use the faster method for their size.
+    ///
+    /// Multiplication chooses this method for large operands. Panics if either operand has less
+    /// than 2 limbs.
+    pub fn mul_karatsuba(&self, other: &Self) -> Self {
+        let split = self.limbs.len().min(other.limbs.len()) / 2;
+        assert!(
+            split > 0,
+            "Karatsuba requires at least 2 limbs in each operand"
+        );
+
+        let (a0, a1) = self.split_limbs(split);
+        let (b0, b1) = other.split_limbs(split);
+
+        let z0 = &a0 * &b0;
+        let z2 = &a1 * &b1;
+        let z1 = (a0 + a1) * (b0 + b1) - &z0 - &z2;
+
+        (z2 << (2 * split * LIMB_BITS)) + (z1 << (split * LIMB_BITS)) + z0
+    }
+
+    /// Returns (low, high), where low is the first split limbs of self, and high is the rest.
+    fn split_limbs(&self, split: usize) -> (Self, Self) {
+        let split = split.min(self.limbs.len());
+        let (low, high) = self.limbs.split_at(split);
+
+        (
+            BigUint::from_limbs(low.to_vec()),
+            BigUint::from_limbs(high.to_vec()),
+        )
+    }
+
+    /// Returns self * other, using schoolbook long multiplication.
+    ///
+    /// This is the reference implementation for multiplication, which is faster for small
+    /// operands.
+    pub fn mul_schoolbook(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let t = *a as DoubleLimb * *b as DoubleLimb + limbs[i + j] as DoubleLimb + carry;
+                limbs[i + j] = t as Limb;
+                carry = t >> LIMB_BITS;
+            }
+            limbs[i + other.limbs.len()] = carry as Limb;
+        }
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns (self / d, self % d).
+    ///
+    /// Panics if d is zero.
+    pub fn divmod(&self, d: &Self) -> (Self, Self) {
+        assert!(!d.is_zero(), "The divisor must not be zero");
+
+        if *self < *d {
+            return (BigUint::zero(), self.clone());
+        }
+        if d.limbs.len() == 1 {
+            let (q, r) = self.divmod_limb(d.limbs[0]);
+            return (q, BigUint::from(r as u64));
+        }
+
+        // Knuth, The Art of Computer Programming, Vol 2, 4.3.1, Algorithm D.
+        // Normalize so the divisor's top limb has its high bit set.
+        let shift = d.limbs.last().expect("d is non-zero").leading_zeros() as usize;
+        let v = (d << shift).limbs;
+        let mut u = (self << shift).limbs;
+        u.resize(self.limbs.len() + 1, 0);
+
+        let n = v.len();
+        let m = u.len() - n;
+        let base: DoubleLimb = 1 << LIMB_BITS;
+        let mut q = vec![0; m];
+
+        for j in (0..m).rev() {
+            // Estimate the quotient digit from the top two limbs
+            let num = (u[j + n] as DoubleLimb) << LIMB_BITS | u[j + n - 1] as DoubleLimb;
+            let mut qhat = num / v[n - 1] as DoubleLimb;
+            let mut rhat = num % v[n - 1] as DoubleLimb;
+            while qhat >= base
+                || qhat * v[n - 2] as DoubleLimb > (rhat << LIMB_BITS | u[j + n - 2] as DoubleLimb)
+            {
+                qhat -= 1;
+                rhat += v[n - 1] as DoubleLimb;
+                if rhat >= base {
+                    break;
+                }
+            }
+
+            // Multiply and subtract
+            let mut borrow: i64 = 0;
+            let mut carry: DoubleLimb = 0;
+            for i in 0..n {
+                let p = qhat * v[i] as DoubleLimb + carry;
+                carry = p >> LIMB_BITS;
+                let t = u[i + j] as i64 - borrow - (p as Limb) as i64;
+                u[i + j] = t as Limb;
+                borrow = (t < 0) as i64;
+            }
+            let t = u[j + n] as i64 - borrow - carry as i64;
+            u[j + n] = t as Limb;
+
+            // The estimate was one too large, add back
+            if t < 0 {
+                qhat -= 1;
+                let mut carry: DoubleLimb = 0;
+                for i in 0..n {
+                    let s = u[i + j] as DoubleLimb + v[i] as DoubleLimb + carry;
+                    u[i + j] = s as Limb;
+                    carry = s >> LIMB_BITS;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as Limb);
+            }
+
+            q[j] = qhat as Limb;
+        }
+
+        u.truncate(n);
+        let r = BigUint::from_limbs(u) >> shift;
+
+        (BigUint::from_limbs(q), r)
+    }
+
+    /// Returns self / d.
+    /// Panics if d is zero.
+    fn div_ref(&self, d: &Self) -> Self {
+        self.divmod(d).0
+    }
+
+    /// Returns self % d.
+    /// Panics if d is zero.
+    fn rem_ref(&self, d: &Self) -> Self {
+        self.divmod(d).1
+    }
+
+    /// Returns self | other.
+    fn bitor_ref(&self, other: &Self) -> Self {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (&self.limbs, &other.limbs)
+        } else {
+            (&other.limbs, &self.limbs)
+        };
+
+        let mut limbs = long.clone();
+        for (l, s) in limbs.iter_mut().zip(short.iter()) {
+            *l |= s;
+        }
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns self & other.
+    fn bitand_ref(&self, other: &Self) -> Self {
+        let limbs = self
+            .limbs
+            .iter()
+            .zip(other.limbs.iter())
+            .map(|(a, b)| a & b)
+            .collect();
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns self << bits.
+    fn shl_bits(&self, bits: usize) -> Self {
+        if self.is_zero() {
+            return BigUint::zero();
+        }
+
+        let limb_shift = bits / LIMB_BITS;
+        let bit_shift = bits % LIMB_BITS;
+
+        let mut limbs = vec![0; limb_shift];
+        if bit_shift == 0 {
+            limbs.extend_from_slice(&self.limbs);
+        } else {
+            let mut carry = 0;
+            for l in &self.limbs {
+                limbs.push(l << bit_shift | carry);
+                carry = l >> (LIMB_BITS - bit_shift);
+            }
+            limbs.push(carry);
+        }
+
+        BigUint::from_limbs(limbs)
+    }
+
+    /// Returns self >> bits.
+    fn shr_bits(&self, bits: usize) -> Self {
+        let limb_shift = bits / LIMB_BITS;
+        let bit_shift = bits % LIMB_BITS;
+
+        if limb_shift >= self.limbs.len() {
+            return BigUint::zero();
+        }
+
+        let high = &self.limbs[limb_shift..];
+        let limbs = if bit_shift == 0 {
+            high.to_vec()
+        } else {
+            high.iter()
+                .enumerate()
+                .map(|(i, l)| {
+                    let next = high.get(i + 1).unwrap_or(&0);
+                    l >> bit_shift | next << (LIMB_BITS - bit_shift)
+                })
+                .collect()
+        };
+
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint::from_limbs(vec![n as Limb, (n >> LIMB_BITS) as Limb])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Normalized limbs mean longer numbers are larger
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_dec_string())
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex())
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BigUint(0x{})", self.to_hex())
+    }
+}
+
+/// Implement a binary operator for all combinations of owned and borrowed BigUints, using a
+/// method that takes two references.
+macro_rules! forward_binop {
+    ($imp:ident, $method:ident, $func:ident) => {
+        impl $imp<&BigUint> for &BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: &BigUint) -> BigUint {
+                BigUint::$func(self, other)
+            }
+        }
+
+        impl $imp<BigUint> for &BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: BigUint) -> BigUint {
+                BigUint::$func(self, &other)
+            }
+        }
+
+        impl $imp<&BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: &BigUint) -> BigUint {
+                BigUint::$func(&self, other)
+            }
+        }
+
+        impl $imp<BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: BigUint) -> BigUint {
+                BigUint::$func(&self, &other)
+            }
+        }
+    };
+}
+
+forward_binop!(Add, add, add_ref);
+forward_binop!(Sub, sub, sub_ref);
+forward_binop!(Mul, mul, mul_ref);
+forward_binop!(Div, div, div_ref);
+forward_binop!(Rem, rem, rem_ref);
+forward_binop!(BitOr, bitor, bitor_ref);
+forward_binop!(BitAnd, bitand, bitand_ref);
+
+impl Shl<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shl(self, bits: usize) -> BigUint {
+        self.shl_bits(bits)
+    }
+}
+
+impl Shl<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, bits: usize) -> BigUint {
+        self.shl_bits(bits)
+    }
+}
+
+impl Shr<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shr(self, bits: usize) -> BigUint {
+        self.shr_bits(bits)
+    }
+}
+
+impl Shr<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, bits: usize) -> BigUint {
+        self.shr_bits(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{biguint_generator, NIST_P_HEX};
+
+    /// The decimal representation of NIST_P_HEX
+    const NIST_P_DEC: &str = "2410312426921032588552076022197566074856950548502459942654116941958108831682612228890093858261341614673227141477904012196503648957050582631942730706805009223062734745341073406696246014589361659774041027169249453200378729434170325843778659198143763193776859869524088940195577346119843545301547043747207749969763750084308926339295559968882457872412993810129130294592999947926365264059284647209730384947211681434464714438488520940127459844288859336526896320919633919";
+
+    fn nist_p() -> BigUint {
+        BigUint::from_hex(NIST_P_HEX)
+    }
+
+    fn big(s: &str) -> BigUint {
+        BigUint::from_hex(s)
+    }
+
+    #[test]
+    fn small() {
+        assert!(BigUint::zero().is_zero());
+        assert!(BigUint::one().is_one());
+        assert_eq!(BigUint::from(0), BigUint::zero());
+        assert_eq!(BigUint::from(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!((BigUint::from(u64::MAX) + BigUint::one()).to_u64(), None);
+
+        assert_eq!(BigUint::from(2) + BigUint::from(3), BigUint::from(5));
+        assert_eq!(BigUint::from(7) - BigUint::from(3), BigUint::from(4));
+        assert_eq!(BigUint::from(6) * BigUint::from(7), BigUint::from(42));
+        assert_eq!(BigUint::from(43) / BigUint::from(7), BigUint::from(6));
+        assert_eq!(BigUint::from(43) % BigUint::from(7), BigUint::from(1));
+        assert_eq!(BigUint::from(1) << 64, big("10000000000000000"));
+        assert_eq!(big("10000000000000000") >> 63, BigUint::from(2));
+        assert_eq!(BigUint::from(5) >> 3, BigUint::zero());
+        assert_eq!(BigUint::from(5) | BigUint::from(3), BigUint::from(7));
+        assert_eq!(BigUint::from(5) & BigUint::from(3), BigUint::from(1));
+        assert_eq!(
+            big("f0000000000000000") | BigUint::from(0xff),
+            big("f00000000000000ff")
+        );
+        assert_eq!(
+            big("f00000000000000ff") & BigUint::from(0x1f0),
+            BigUint::from(0xf0)
+        );
+        assert_eq!(
+            big("f0000000000000000") & BigUint::from(0xff),
+            BigUint::zero()
+        );
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert_eq!(BigUint::one().bits(), 1);
+        assert_eq!(BigUint::from(255).bits(), 8);
+        assert_eq!(BigUint::from(256).bits(), 9);
+        assert_eq!(nist_p().bits(), 1536);
+
+        assert!(BigUint::from(5).bit(0));
+        assert!(!BigUint::from(5).bit(1));
+        assert!(BigUint::from(5).bit(2));
+        assert!(!BigUint::from(5).bit(1000));
+        assert!(BigUint::zero().is_even());
+        assert!(!nist_p().is_even());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(BigUint::zero() < BigUint::one());
+        assert!(BigUint::from(u64::MAX) < big("10000000000000000"));
+        assert!(big("100000000") > big("ffffffff"));
+        assert!(nist_p() > nist_p() - BigUint::one());
+        assert_eq!(nist_p().cmp(&nist_p()), Ordering::Equal);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(BigUint::zero().to_bytes_be(), [0]);
+        assert_eq!(BigUint::from_bytes_be(&[]), BigUint::zero());
+        assert_eq!(BigUint::from_bytes_be(&[0, 0, 1]), BigUint::one());
+        assert_eq!(
+            BigUint::from_bytes_be(&[1, 2, 3, 4, 5]).to_bytes_be(),
+            [1, 2, 3, 4, 5]
+        );
+        assert_eq!(BigUint::from(0x0102).to_bytes_be(), [1, 2]);
+
+        let p_bytes = hex::hex_decode(NIST_P_HEX);
+        assert_eq!(BigUint::from_bytes_be(&p_bytes), nist_p());
+        assert_eq!(nist_p().to_bytes_be(), p_bytes);
+    }
+
+    #[test]
+    fn hex_strings() {
+        assert_eq!(BigUint::zero().to_hex(), "0");
+        assert_eq!(BigUint::from_hex("0").to_hex(), "0");
+        assert_eq!(BigUint::from_hex("000abc").to_hex(), "abc");
+        assert_eq!(BigUint::from_hex("ABC"), BigUint::from(0xabc));
+        assert_eq!(nist_p().to_hex(), NIST_P_HEX);
+
+        assert_eq!(format!("{:x}", BigUint::from(255)), "ff");
+        assert_eq!(format!("{:#x}", BigUint::from(255)), "0xff");
+        assert_eq!(format!("{:?}", BigUint::from(255)), "BigUint(0xff)");
+    }
+
+    #[test]
+    fn dec_strings() {
+        assert_eq!(BigUint::zero().to_dec_string(), "0");
+        assert_eq!(BigUint::from_dec_str("000").to_dec_string(), "0");
+        assert_eq!(
+            BigUint::from_dec_str("1000000000"),
+            BigUint::from(1_000_000_000)
+        );
+        assert_eq!(
+            BigUint::from(u64::MAX).to_dec_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(
+            BigUint::from_dec_str("18446744073709551616"),
+            big("10000000000000000")
+        );
+        assert_eq!(BigUint::from_dec_str(NIST_P_DEC), nist_p());
+        assert_eq!(nist_p().to_dec_string(), NIST_P_DEC);
+        assert_eq!(format!("{}", nist_p()), NIST_P_DEC);
+        assert_eq!(format!("{:>5}", BigUint::from(42)), "   42");
+    }
+
+    #[test]
+    fn nist_prime_arithmetic() {
+        let p = nist_p();
+
+        assert_eq!(
+            &p + BigUint::one(),
+            big("ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca2373280000000000000000")
+        );
+        assert_eq!(
+            &p - (BigUint::one() << 1535),
+            big("7fffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff")
+        );
+        assert_eq!(
+            &p * &p,
+            big("ffffffffffffffff921fb54442d184699556f620b115a80b1f5a43d393a37a0cba694b7b42283f8504feb4a8fa080a06ad9fd90cc3df8dcba988d9153adda61b3f58a9ca013666664e95cc8efd32f3851241e9a23e32083c65e695bef27bf7caa26ef814ca0fae5bfbac779564030f13a8ca2fdde2271c6898c39fa71ee78399cfd0a5a3cb64fb29f71a850d5c7ca819df708cbd5d67a88f38aedc42425512a5e892dc6c51f9d158ed78588354ae83726b1e3d01a00c083fc1a2d2b3da5277ffc61ddb3245169897699d13ed82961c8335310dfce163d8cccad09f435f0fd0f071fc4677de814c3b02f9fe7b0df9da5602d00b9218a18cea7bafd1c00165d1d5bc3f9ff3dc617c0c992b57f543bb9e75880bca4072844b0197a5e9d90a4528752eef7e3d2ddce02d1bbf38a918ea3006130f6019b2e45fd5f3ffda75456578c8b0397fd970742230feef7e32898d6a288bfee9023d59b4a779f9fbd867410dea0ace05a06d93463209247e873b43c6361d1727ee6bb919b00000000000000001")
+        );
+        assert_eq!(
+            &p >> 100,
+            big("ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c0")
+        );
+        assert_eq!(
+            &p << 33,
+            big("1ffffffffffffffff921fb54442d18469898cc51701b839a252049c1114cf98e804177d4c76273644a29410f31c6809bbdf2a33679a748636605614dbe4be286e9fc26adadaa3848bc90b6aecc4bcfd8de89885d34c6fdad617feb96de80d6fdbdc70d7f6b5133f4b5d3e4822f8963fcc9250cca3d9c8b67b8400f97142c77e0b31b4906c38aba734d22c7f51fa499ebf06caba47b9475b2c38c5e6ac410aa5773daa520ee12d2cdace186a9c95793009e2e8d8119446e64ffffffffffffffffe00000000")
+        );
+
+        // A 201-bit divisor
+        let a = (BigUint::one() << 200) + BigUint::from(12345678901234567890);
+        let (q, r) = p.divmod(&a);
+        assert_eq!(
+            q,
+            big("ffffffffffffffffc90fdaa22168c234c41b0de1f3f0fdc65727129caf24bd06e4ca184c8008a1682803631547a856e662d09b641c4bb2fcd02d56a94f013d98b7ab2cba8b57409c508cd9cb3c255c55189885652bfbfbf004923bba40bf14ceca02d02f14f4701aa91141a4d57c8d907bfb18bef0e0e5f71e54bf45fb1bf6be444ad587bd579c791493e9d2e00742bbbbf6287c6f8977bfcb604a769d33b3eec3ad53a9006b11")
+        );
+        assert_eq!(r, big("ea4ef63b61cddb7d21da54bed0686cb87385f4ed23c76a820d"));
+
+        // A 128-bit divisor
+        let b = big("123456789abcdef0fedcba9876543210");
+        assert_eq!(&p % &b, big("27becf3403042aafab971a56b4bbb5f"));
+        assert_eq!(
+            &p / &b,
+            big("e1000000000000c752b6ef287f5dc36dd8286d858c65bef32e2d34d8807fc8e60fb9c1047ed5a065ecfb0396cce929c2226c794a3b4a9bb97e8a5d6668447c7daf7948796684a37887da6abc8bcd84cfa3ea6385ebcf4850e687458059798294e467d431558277064003c0f1d7740eff4538c42eb79b898568d4a2193d4f8d309aaad1aa34d6178e72ffd5c72304b06250abd4c65d3e2b300450cdfc3db0c8e869942af3e078551bc1a3031d9cb27050a")
+        );
+    }
+
+    #[test]
+    fn divmod_identity() {
+        // Deterministic pseudo-random operands, with a mix of lengths and limb values
+        let mut next = biguint_generator(0x1234_5678_9abc_def0);
+
+        for n_len in [1, 4, 8, 9, 16, 33, 64, 200] {
+            for d_len in [1, 4, 5, 8, 12, 32, 64] {
+                let n = next(n_len);
+                let d = next(d_len) + BigUint::one();
+                let (q, r) = n.divmod(&d);
+                assert!(r < d);
+                assert_eq!(&q * &d + &r, n);
+            }
+        }
+    }
+
+    #[test]
+    fn karatsuba() {
+        let mut next = biguint_generator(0x0fed_cba9_8765_4321);
+
+        // Balanced and unbalanced operands, above and below the threshold
+        let limb_bytes = KARATSUBA_THRESHOLD * LIMB_BYTES;
+        for (a_len, b_len) in [(2, 2), (1, 1), (2, 3), (4, 4), (8, 3), (10, 10)] {
+            let a = next(a_len * limb_bytes);
+            let b = next(b_len * limb_bytes);
+            assert_eq!(a.mul_karatsuba(&b), a.mul_schoolbook(&b));
+            assert_eq!(&a * &b, a.mul_schoolbook(&b));
+        }
+
+        // All-ones limbs maximise the carries
+        let ones = (BigUint::one() << (4 * limb_bytes * BYTE_BITS)) - BigUint::one();
+        assert_eq!(ones.mul_karatsuba(&ones), ones.mul_schoolbook(&ones));
+
+        let small = BigUint::from_hex("123456789abcdef0123");
+        assert_eq!(small.mul_karatsuba(&small), small.mul_schoolbook(&small));
+    }
+
+    #[test]
+    #[should_panic(expected = "divisor must not be zero")]
+    fn invalid_div_zero() {
+        let _ = BigUint::one() / BigUint::zero();
+    }
+
+    #[test]
+    #[should_panic(expected = "subtraction underflow")]
+    fn invalid_sub_underflow() {
+        let _ = BigUint::one() - BigUint::from(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid hex character")]
+    fn invalid_hex() {
+        BigUint::from_hex("12g4");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid decimal character")]
+    fn invalid_dec() {
+        BigUint::from_dec_str("12a4");
+    }
+}
@@ -0,0 +1,420 @@
+//! Modular arithmetic on arbitrary-precision unsigned integers
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use crate::math::BigUint;
+
+/// The reasons a modular arithmetic operation can fail
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModularError {
+    /// The modulus was zero
+    ZeroModulus,
+    /// The value has no inverse, because it shares the factor gcd with the modulus
+    NoInverse {
+        /// The value that was inverted
+        a: BigUint,
+        /// The modulus
+        m: BigUint,
+        /// The greatest common divisor of a and m, which is greater than one
+        gcd: BigUint,
+    },
+}
+
+impl fmt::Display for ModularError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModularError::ZeroModulus => write!(f, "The modulus must not be zero"),
+            ModularError::NoInverse { a, m, gcd } => write!(
+                f,
+                "{} has no inverse modulo {}, because they have common factor {}",
+                a, m, gcd
+            ),
+        }
+    }
+}
+
+/// The result of the extended Euclidean algorithm on a and b.
+///
+/// The Bezout coefficients satisfy a * x + b * y = gcd. BigUint is unsigned, so the signs of the
+/// coefficients are stored separately.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtendedGcd {
+    /// The greatest common divisor of a and b
+    pub gcd: BigUint,
+    /// The magnitude of the coefficient of a
+    pub x: BigUint,
+    /// True if the coefficient of a is negative
+    pub x_negative: bool,
+    /// The magnitude of the coefficient of b
+    pub y: BigUint,
+    /// True if the coefficient of b is negative
+    pub y_negative: bool,
+}
+
+/// A signed value, stored as (negative, magnitude).
+/// Zero is always stored as non-negative.
+type Signed = (bool, BigUint);
+
+/// Returns a - q * b, for signed a and b, and unsigned q.
+fn signed_sub_mul(a: &Signed, q: &BigUint, b: &Signed) -> Signed {
+    let (a_neg, a_mag) = a;
+    let (b_neg, b_mag) = b;
+    let qb = q * b_mag;
+
+    // a - (-qb) = a + qb, and -a - qb = -(a + qb)
+    let (neg, mag) = if a_neg != b_neg {
+        (*a_neg, a_mag + &qb)
+    } else if *a_mag >= qb {
+        (*a_neg, a_mag - &qb)
+    } else {
+        (!a_neg, &qb - a_mag)
+    };
+
+    (neg && !mag.is_zero(), mag)
+}
+
+impl BigUint {
+    /// Returns self ^ e.
+    pub fn pow(&self, e: u32) -> Self {
+        let mut result = BigUint::one();
+        for i in (0..u32::BITS - e.leading_zeros()).rev() {
+            result = &result * &result;
+            if (e >> i) & 1 == 1 {
+                result = &result * self;
+            }
+        }
+
+        result
+    }
+
+    /// Returns self ^ e mod m, using left-to-right square-and-multiply, and a division after each
+    /// multiplication.
+    ///
+    /// This is the reference implementation for modpow(), which is faster for odd moduli.
+    ///
+    /// Panics if m is zero.
+    pub fn modpow_naive(&self, e: &Self, m: &Self) -> Self {
+        assert!(!m.is_zero(), "{}", ModularError::ZeroModulus);
+
+        let base = self % m;
+        let mut result = BigUint::one() % m;
+        for i in (0..e.bits()).rev() {
+            result = &result * &result % m;
+            if e.bit(i) {
+                result = &result * &base % m;
+            }
+        }
+
+        result
+    }
+
+    /// Returns the greatest common divisor of self and other.
+    /// The gcd of zero and zero is zero.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+
+        a
+    }
+
+    /// Returns the gcd of self and other, and the Bezout coefficients x and y, such that
+    /// self * x + other * y = gcd.
+    pub fn egcd(&self, other: &Self) -> ExtendedGcd {
+        let mut old_r = self.clone();
+        let mut r = other.clone();
+        let mut old_s: Signed = (false, BigUint::one());
+        let mut s: Signed = (false, BigUint::zero());
+        let mut old_t: Signed = (false, BigUint::zero());
+        let mut t: Signed = (false, BigUint::one());
+
+        while !r.is_zero() {
+            let (q, next_r) = old_r.divmod(&r);
+            old_r = core::mem::replace(&mut r, next_r);
+
+            let next_s = signed_sub_mul(&old_s, &q, &s);
+            old_s = core::mem::replace(&mut s, next_s);
+
+            let next_t = signed_sub_mul(&old_t, &q, &t);
+            old_t = core::mem::replace(&mut t, next_t);
+        }
+
+        ExtendedGcd {
+            gcd: old_r,
+            x: old_s.1,
+            x_negative: old_s.0,
+            y: old_t.1,
+            y_negative: old_t.0,
+        }
+    }
+
+    /// Returns the inverse of self modulo m: the value x in [0, m), where self * x = 1 mod m.
+    ///
+    /// Returns an error if m is zero, or if self and m are not coprime.
+    pub fn invmod(&self, m: &Self) -> Result<Self, ModularError> {
+        if m.is_zero() {
+            return Err(ModularError::ZeroModulus);
+        }
+
+        let e = self.egcd(m);
+        if !e.gcd.is_one() {
+            return Err(ModularError::NoInverse {
+                a: self.clone(),
+                m: m.clone(),
+                gcd: e.gcd,
+            });
+        }
+
+        let x = e.x % m;
+        if e.x_negative && !x.is_zero() {
+            Ok(m - x)
+        } else {
+            Ok(x)
+        }
+    }
+
+    /// Returns the integer nth root of self: the largest r, where r ^ n <= self.
+    ///
+    /// Panics if n is zero.
+    pub fn nth_root(&self, n: u32) -> Self {
+        assert!(n > 0, "The root must not be zero");
+
+        if self.is_zero() || n == 1 {
+            return self.clone();
+        }
+
+        // Newton's method, starting with a power of two that is greater than or equal to the root.
+        // The iterates decrease monotonically until they reach the root.
+        let n_big = BigUint::from(n as u64);
+        let n_minus_one = BigUint::from(n as u64 - 1);
+        let mut x = BigUint::one() << self.bits().div_ceil(n as usize);
+        loop {
+            let next = (&n_minus_one * &x + self / x.pow(n - 1)) / &n_big;
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+}
+
+/// Combine the congruences x = residue mod modulus, using the Chinese Remainder Theorem.
+///
+/// Returns the unique solution x in [0, M), and the product of the moduli M.
+/// If residues is empty, returns (0, 1).
+///
+/// Returns an error if any modulus is zero, or if the moduli are not pairwise coprime.
+pub fn crt(residues: &[(BigUint, BigUint)]) -> Result<(BigUint, BigUint), ModularError> {
+    let mut x = BigUint::zero();
+    let mut product = BigUint::one();
+
+    for (residue, modulus) in residues {
+        if modulus.is_zero() {
+            return Err(ModularError::ZeroModulus);
+        }
+
+        // Solve x + product * k = residue mod modulus, for k
+        let inverse = (&product % modulus).invmod(modulus)?;
+        let residue = residue % modulus;
+        let x_mod = &x % modulus;
+        let diff = if residue >= x_mod {
+            residue - x_mod
+        } else {
+            modulus - (x_mod - residue)
+        };
+        let k = diff * inverse % modulus;
+
+        x = x + &product * k;
+        product = product * modulus;
+    }
+
+    Ok((x, product))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{string::String, vec::Vec};
+
+    use crate::test_util::NIST_P_HEX;
+
+    fn n(v: u64) -> BigUint {
+        BigUint::from(v)
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(n(3).pow(0), n(1));
+        assert_eq!(n(3).pow(4), n(81));
+        assert_eq!(n(0).pow(0), n(1));
+        assert_eq!(n(2).pow(100), BigUint::one() << 100);
+    }
+
+    #[test]
+    fn modpow() {
+        assert_eq!(n(4).modpow(&n(13), &n(497)), n(445));
+        assert_eq!(n(5).modpow(&n(0), &n(7)), n(1));
+        assert_eq!(n(5).modpow(&n(0), &n(1)), n(0));
+        assert_eq!(n(0).modpow(&n(5), &n(7)), n(0));
+
+        let p = BigUint::from_hex(NIST_P_HEX);
+        let e = BigUint::from_hex("123456789abcdef0fedcba9876543210");
+        assert_eq!(
+            n(2).modpow(&e, &p),
+            BigUint::from_hex("c410428f0f7306001b707479c8a374833e91b5930aabacbaa6d130795b69f473db83631cd65b1e41d0a3c81bfbca1a2c87682f2c60320aff1551eaed29f2112f6203870e86552a830a8de557495473ce25318f73da2e61552f97374d958594cf820dc84c32778ddda5835d85d277424c04f7a6267ea91816c4e0684cb720df1b1b9f92f19d08ab9c64398fbb27188aa8f2e46a67fc9967af69e28a5491515417d9c5f80daee497b62d61e1e8ae0be46160a013319552a9a5ea54159cb00ee5ef")
+        );
+
+        // Fermat's little theorem
+        assert_eq!(e.modpow(&(&p - BigUint::one()), &p), n(1));
+    }
+
+    #[test]
+    fn gcd() {
+        assert_eq!(n(0).gcd(&n(0)), n(0));
+        assert_eq!(n(0).gcd(&n(5)), n(5));
+        assert_eq!(n(12).gcd(&n(18)), n(6));
+        assert_eq!(n(17).gcd(&n(3120)), n(1));
+    }
+
+    #[test]
+    fn egcd() {
+        for (a, b) in [(240, 46), (46, 240), (17, 3120), (0, 5), (5, 0), (7, 7)] {
+            let e = n(a).egcd(&n(b));
+            assert_eq!(e.gcd, n(a).gcd(&n(b)));
+
+            // a * x + b * y = gcd, rearranged to avoid negative values
+            let mut lhs = e.gcd.clone();
+            let mut rhs = BigUint::zero();
+            let ax = n(a) * &e.x;
+            let by = n(b) * &e.y;
+            if e.x_negative {
+                lhs = lhs + ax;
+            } else {
+                rhs = rhs + ax;
+            }
+            if e.y_negative {
+                lhs = lhs + by;
+            } else {
+                rhs = rhs + by;
+            }
+            assert_eq!(lhs, rhs, "egcd({}, {}) = {:?}", a, b, e);
+        }
+    }
+
+    #[test]
+    fn invmod() {
+        assert_eq!(n(17).invmod(&n(3120)), Ok(n(2753)));
+        assert_eq!(n(3).invmod(&n(11)), Ok(n(4)));
+        assert_eq!(n(1).invmod(&n(2)), Ok(n(1)));
+        assert_eq!(n(5).invmod(&n(1)), Ok(n(0)));
+
+        let p = BigUint::from_hex(NIST_P_HEX);
+        let a = BigUint::from_hex("123456789abcdef0fedcba9876543210");
+        let inverse = a.invmod(&p).expect("p is prime");
+        assert_eq!(
+            inverse,
+            BigUint::from_hex("c5b45a2d29b79348bb26c6bc9b5a0f426ffa8ac55f99f1b9c885545b0078089adc314c5a58aeda7e50988aae471b2493dc2ef9360caa691397eb3d219418301c5a1d97abea59c2c48fa99a189c8de5fbb9f7d071e343a36be3f988bb52a76d438e2aab149ab8d835c86867cb25d4a3b0bbe71fb4697b73d2adbb8ba1426d9d548a6de7cbb107abb14990caf6797a98bf8f745578e8444c747782c5ea325d7855f4d9b9283db7a9e50398b438ebea8f695f08d39bc8e508aae957ae59c1d53886")
+        );
+        assert_eq!(a * inverse % p, n(1));
+    }
+
+    #[test]
+    fn invmod_errors() {
+        assert_eq!(
+            n(6).invmod(&n(9)),
+            Err(ModularError::NoInverse {
+                a: n(6),
+                m: n(9),
+                gcd: n(3)
+            })
+        );
+        assert_eq!(
+            n(0).invmod(&n(7)).map_err(|e| format!("{}", e)),
+            Err(String::from(
+                "0 has no inverse modulo 7, because they have common factor 7"
+            ))
+        );
+        assert_eq!(n(3).invmod(&n(0)), Err(ModularError::ZeroModulus));
+    }
+
+    #[test]
+    fn crt() {
+        assert_eq!(super::crt(&[]), Ok((n(0), n(1))));
+        assert_eq!(
+            super::crt(&[(n(2), n(3)), (n(3), n(5)), (n(2), n(7))]),
+            Ok((n(23), n(105)))
+        );
+        // Residues larger than their moduli are reduced
+        assert_eq!(
+            super::crt(&[(n(5), n(3)), (n(8), n(5)), (n(9), n(7))]),
+            Ok((n(23), n(105)))
+        );
+
+        // The e=3 RSA broadcast attack: recover m ^ 3 from three coprime moduli
+        let m = BigUint::from_hex("deadbeefcafebabe1234567890abcdef");
+        let moduli = [n(1_000_000_007), n(998_244_353), n(4_294_967_291)];
+        let c = m.pow(3);
+        let residues: Vec<_> = moduli.iter().map(|p| (&c % p, p.clone())).collect();
+        let (x, product) = super::crt(&residues).expect("moduli are coprime");
+        assert_eq!(x, &c % &product);
+    }
+
+    #[test]
+    fn crt_errors() {
+        assert!(matches!(
+            super::crt(&[(n(1), n(4)), (n(3), n(6))]),
+            Err(ModularError::NoInverse { .. })
+        ));
+        assert_eq!(
+            super::crt(&[(n(1), n(4)), (n(3), n(0))]),
+            Err(ModularError::ZeroModulus)
+        );
+    }
+
+    #[test]
+    fn nth_root() {
+        assert_eq!(n(0).nth_root(3), n(0));
+        assert_eq!(n(1).nth_root(3), n(1));
+        assert_eq!(n(7).nth_root(1), n(7));
+        assert_eq!(n(26).nth_root(3), n(2));
+        assert_eq!(n(27).nth_root(3), n(3));
+        assert_eq!(n(28).nth_root(3), n(3));
+        assert_eq!(n(99).nth_root(2), n(9));
+        assert_eq!(n(100).nth_root(2), n(10));
+
+        let m = BigUint::from_hex("deadbeefcafebabe1234567890abcdef");
+        let cube = m.pow(3);
+        assert_eq!(cube.nth_root(3), m);
+        assert_eq!((&cube - BigUint::one()).nth_root(3), &m - BigUint::one());
+        assert_eq!((cube + BigUint::from(12345)).nth_root(3), m);
+        assert_eq!(
+            BigUint::from_hex("a87ba575e634a9da6310885639fbfdde75ff86a2e8ab24771e0afd1839771f64e36ff491c67fdc0a0cbf43ea4bcec708").nth_root(3),
+            m
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus must not be zero")]
+    fn invalid_modpow_zero_modulus() {
+        n(2).modpow(&n(3), &n(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus must not be zero")]
+    fn invalid_modpow_naive_zero_modulus() {
+        n(2).modpow_naive(&n(3), &n(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "root must not be zero")]
+    fn invalid_nth_root_zero() {
+        n(2).nth_root(0);
+    }
+}
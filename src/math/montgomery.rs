@@ -0,0 +1,273 @@
+//! Montgomery-form modular multiplication and exponentiation
+
+#![deny(missing_docs)]
+
+use alloc::{vec, vec::Vec};
+
+use crate::math::biguint::{DoubleLimb, Limb, LIMB_BITS};
+use crate::math::BigUint;
+
+/// The largest sliding window used for exponentiation, in bits
+const MAX_WINDOW_BITS: usize = 6;
+
+/// A Montgomery multiplication context for an odd modulus m.
+///
+/// Values are stored in Montgomery form: x * R mod m, where R = 2 ^ (LIMB_BITS * limb count).
+/// Multiplying two values in Montgomery form only needs shifts, rather than a division by m.
+#[derive(Clone, Debug)]
+pub struct Montgomery {
+    /// The modulus, as exactly limb_count limbs
+    modulus: Vec<Limb>,
+    /// -m ^ -1 mod 2 ^ LIMB_BITS
+    m_inv: Limb,
+    /// R ^ 2 mod m, used to convert values into Montgomery form
+    r_squared: Vec<Limb>,
+}
+
+impl Montgomery {
+    /// Create a Montgomery context for the modulus m.
+    ///
+    /// Returns None if m is even, because it has no inverse modulo R. This includes zero.
+    pub fn new(m: &BigUint) -> Option<Self> {
+        if m.is_even() {
+            return None;
+        }
+
+        let limb_count = m.limbs.len();
+
+        // Newton's method doubles the number of correct low bits each iteration:
+        // 1 -> 2 -> 4 -> 8 -> 16 -> 32
+        let m0 = m.limbs[0];
+        let mut inv: Limb = 1;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul((2 as Limb).wrapping_sub(m0.wrapping_mul(inv)));
+        }
+        assert_eq!(m0.wrapping_mul(inv), 1, "Odd numbers are invertible mod R");
+
+        let r_squared = (BigUint::one() << (2 * LIMB_BITS * limb_count)) % m;
+
+        Some(Montgomery {
+            modulus: m.limbs.clone(),
+            m_inv: inv.wrapping_neg(),
+            r_squared: Montgomery::pad(&r_squared, limb_count),
+        })
+    }
+
+    /// Returns the limbs of x, zero-extended to limb_count limbs.
+    fn pad(x: &BigUint, limb_count: usize) -> Vec<Limb> {
+        assert!(x.limbs.len() <= limb_count);
+
+        let mut limbs = x.limbs.clone();
+        limbs.resize(limb_count, 0);
+        limbs
+    }
+
+    /// Returns the modulus m.
+    pub fn modulus(&self) -> BigUint {
+        BigUint::from_limbs(self.modulus.clone())
+    }
+
+    /// Returns a * b * R ^ -1 mod m, for a and b in [0, m).
+    ///
+    /// Uses the Coarsely Integrated Operand Scanning (CIOS) method.
+    fn mul(&self, a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+        let n = self.modulus.len();
+        let m = &self.modulus;
+        let mut t = vec![0 as Limb; n + 2];
+
+        for a_i in a {
+            // t += a_i * b
+            let mut carry: DoubleLimb = 0;
+            for j in 0..n {
+                let s = t[j] as DoubleLimb + *a_i as DoubleLimb * b[j] as DoubleLimb + carry;
+                t[j] = s as Limb;
+                carry = s >> LIMB_BITS;
+            }
+            let s = t[n] as DoubleLimb + carry;
+            t[n] = s as Limb;
+            t[n + 1] = (s >> LIMB_BITS) as Limb;
+
+            // t = (t + u * m) / 2 ^ LIMB_BITS, where u makes the low limb zero
+            let u = t[0].wrapping_mul(self.m_inv);
+            let s = t[0] as DoubleLimb + u as DoubleLimb * m[0] as DoubleLimb;
+            let mut carry = s >> LIMB_BITS;
+            for j in 1..n {
+                let s = t[j] as DoubleLimb + u as DoubleLimb * m[j] as DoubleLimb + carry;
+                t[j - 1] = s as Limb;
+                carry = s >> LIMB_BITS;
+            }
+            let s = t[n] as DoubleLimb + carry;
+            t[n - 1] = s as Limb;
+            t[n] = t[n + 1] + (s >> LIMB_BITS) as Limb;
+        }
+
+        // t < 2m, so at most one subtraction is needed
+        t.truncate(n + 1);
+        if t[n] != 0 || !Montgomery::less_than(&t[..n], m) {
+            let mut borrow = 0;
+            for j in 0..n {
+                let (d, b1) = t[j].overflowing_sub(m[j]);
+                let (d, b2) = d.overflowing_sub(borrow);
+                t[j] = d;
+                borrow = (b1 || b2) as Limb;
+            }
+        }
+        t.truncate(n);
+
+        t
+    }
+
+    /// Returns true if a < b, for equal-length little-endian limbs.
+    fn less_than(a: &[Limb], b: &[Limb]) -> bool {
+        a.iter().rev().lt(b.iter().rev())
+    }
+
+    /// Returns x converted into Montgomery form: x * R mod m.
+    fn enter(&self, x: &BigUint) -> Vec<Limb> {
+        let n = self.modulus.len();
+        let x = Montgomery::pad(&(x % &self.modulus()), n);
+        self.mul(&x, &self.r_squared)
+    }
+
+    /// Returns x converted out of Montgomery form: x * R ^ -1 mod m.
+    fn leave(&self, x: &[Limb]) -> BigUint {
+        let mut one = vec![0; self.modulus.len()];
+        one[0] = 1;
+        BigUint::from_limbs(self.mul(x, &one))
+    }
+
+    /// Returns a * b mod m.
+    pub fn mul_mod(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        // (aR)(b)R^-1 = ab
+        let a = self.enter(a);
+        let b = Montgomery::pad(&(b % &self.modulus()), self.modulus.len());
+        BigUint::from_limbs(self.mul(&a, &b))
+    }
+
+    /// Returns base ^ e mod m, using sliding window exponentiation.
+    pub fn modpow(&self, base: &BigUint, e: &BigUint) -> BigUint {
+        let bits = e.bits();
+        let window_bits = match bits {
+            0..=32 => 1,
+            33..=128 => 3,
+            129..=512 => 4,
+            513..=1024 => 5,
+            _ => MAX_WINDOW_BITS,
+        };
+
+        // Precompute the odd powers base ^ 1, base ^ 3, ..., base ^ (2 ^ window_bits - 1)
+        let base = self.enter(base);
+        let base_squared = self.mul(&base, &base);
+        let mut odd_powers = Vec::with_capacity(1 << (window_bits - 1));
+        odd_powers.push(base);
+        for i in 1..(1 << (window_bits - 1)) {
+            let next = self.mul(&odd_powers[i - 1], &base_squared);
+            odd_powers.push(next);
+        }
+
+        // R mod m is one in Montgomery form
+        let mut result = self.enter(&BigUint::one());
+
+        // Scan the exponent from the most significant bit
+        let mut i = bits;
+        while i > 0 {
+            if !e.bit(i - 1) {
+                result = self.mul(&result, &result);
+                i -= 1;
+                continue;
+            }
+
+            // Find the longest window ending in a set bit, which is at most window_bits long
+            let mut low = i.saturating_sub(window_bits);
+            while !e.bit(low) {
+                low += 1;
+            }
+            let mut window = 0;
+            for j in (low..i).rev() {
+                result = self.mul(&result, &result);
+                window = window << 1 | e.bit(j) as usize;
+            }
+            result = self.mul(&result, &odd_powers[window >> 1]);
+            i = low;
+        }
+
+        self.leave(&result)
+    }
+}
+
+impl BigUint {
+    /// Returns self ^ e mod m.
+    ///
+    /// Uses Montgomery multiplication and sliding window exponentiation if m is odd, and
+    /// square-and-multiply otherwise.
+    ///
+    /// Panics if m is zero.
+    pub fn modpow(&self, e: &Self, m: &Self) -> Self {
+        match Montgomery::new(m) {
+            // Montgomery form has no advantage for single-limb moduli
+            Some(ctx) if m.limbs.len() > 1 => ctx.modpow(self, e),
+            _ => self.modpow_naive(e, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{biguint_generator, NIST_P_HEX};
+
+    #[test]
+    fn new() {
+        assert!(Montgomery::new(&BigUint::zero()).is_none());
+        assert!(Montgomery::new(&BigUint::from(10)).is_none());
+        assert!(Montgomery::new(&BigUint::from(11)).is_some());
+
+        let p = BigUint::from_hex(NIST_P_HEX);
+        assert_eq!(Montgomery::new(&p).expect("p is odd").modulus(), p);
+    }
+
+    #[test]
+    fn mul_mod() {
+        let mut next = biguint_generator(1);
+        for len in [4, 8, 31, 64, 128, 256] {
+            let m = (next(len) << 1) + BigUint::one();
+            let ctx = Montgomery::new(&m).expect("m is odd");
+            let a = next(len + 3);
+            let b = next(len);
+            assert_eq!(ctx.mul_mod(&a, &b), a * b % m);
+        }
+    }
+
+    #[test]
+    fn modpow_matches_naive() {
+        let mut next = biguint_generator(2);
+        for (m_len, e_len) in [(8, 1), (8, 8), (16, 5), (64, 64), (128, 128), (256, 3)] {
+            let m = (next(m_len) << 1) + BigUint::one();
+            let base = next(m_len + 1);
+            let e = next(e_len) + BigUint::one();
+            assert_eq!(
+                m.modpow(&e, &m),
+                BigUint::zero(),
+                "m ^ e mod m is zero for non-zero e"
+            );
+            assert_eq!(base.modpow(&e, &m), base.modpow_naive(&e, &m));
+        }
+    }
+
+    #[test]
+    fn modpow_edge_cases() {
+        let p = BigUint::from_hex(NIST_P_HEX);
+        let two = BigUint::from(2);
+
+        assert_eq!(two.modpow(&BigUint::zero(), &p), BigUint::one());
+        assert_eq!(two.modpow(&BigUint::one(), &p), two);
+        assert_eq!(BigUint::zero().modpow(&two, &p), BigUint::zero());
+        assert_eq!((&p + &two).modpow(&two, &p), BigUint::from(4));
+        // Fermat's little theorem
+        assert_eq!(two.modpow(&(&p - BigUint::one()), &p), BigUint::one());
+        // Even moduli use the naive path
+        let even = &p + BigUint::one();
+        assert_eq!(two.modpow(&p, &even), two.modpow_naive(&p, &even));
+    }
+}
@@ -0,0 +1,363 @@
+//! Primality testing and random prime generation
+
+#![deny(missing_docs)]
+
+use alloc::vec;
+
+use crate::math::biguint::Limb;
+use crate::math::{BigUint, Montgomery, BYTE_BITS};
+use crate::rng::Rng;
+
+/// The number of primes less than SMALL_PRIME_LIMIT
+const SMALL_PRIME_COUNT: usize = 168;
+/// The upper bound on the primes used for trial division
+const SMALL_PRIME_LIMIT: usize = 1000;
+
+/// The primes less than SMALL_PRIME_LIMIT, used for trial division
+const SMALL_PRIMES: [Limb; SMALL_PRIME_COUNT] = small_primes();
+
+/// The default number of Miller-Rabin rounds.
+/// The probability that a composite passes every round is at most 4 ^ -MILLER_RABIN_ROUNDS.
+pub const MILLER_RABIN_ROUNDS: usize = 40;
+
+/// Returns the primes less than SMALL_PRIME_LIMIT, using the Sieve of Eratosthenes.
+const fn small_primes() -> [Limb; SMALL_PRIME_COUNT] {
+    let mut composite = [false; SMALL_PRIME_LIMIT];
+    let mut primes = [0; SMALL_PRIME_COUNT];
+    let mut count = 0;
+
+    let mut i = 2;
+    while i < SMALL_PRIME_LIMIT {
+        if !composite[i] {
+            primes[count] = i as Limb;
+            count += 1;
+
+            let mut j = i * i;
+            while j < SMALL_PRIME_LIMIT {
+                composite[j] = true;
+                j += i;
+            }
+        }
+        i += 1;
+    }
+
+    assert!(count == SMALL_PRIME_COUNT);
+    primes
+}
+
+/// The result of trial division by SMALL_PRIMES
+enum TrialDivision {
+    /// The number is one of SMALL_PRIMES
+    SmallPrime,
+    /// The number is divisible by one of SMALL_PRIMES, or less than 2
+    Composite,
+    /// The number has no small factors
+    Unknown,
+}
+
+impl BigUint {
+    /// Returns a uniformly random BigUint with at most bits significant bits.
+    pub fn random_bits<R: Rng>(bits: usize, rng: &mut R) -> Self {
+        let byte_count = bits.div_ceil(BYTE_BITS);
+        let mut bytes = vec![0; byte_count];
+        rng.fill_bytes(&mut bytes);
+
+        // Clear the excess high bits
+        let excess = byte_count * BYTE_BITS - bits;
+        if let Some(top) = bytes.first_mut() {
+            *top &= 0xff >> excess;
+        }
+
+        BigUint::from_bytes_be(&bytes)
+    }
+
+    /// Returns a uniformly random BigUint in [0, bound), using rejection sampling.
+    ///
+    /// Panics if bound is zero.
+    pub fn random_below<R: Rng>(bound: &Self, rng: &mut R) -> Self {
+        assert!(!bound.is_zero(), "The bound must not be zero");
+
+        // Each attempt succeeds with probability greater than 1/2
+        loop {
+            let candidate = BigUint::random_bits(bound.bits(), rng);
+            if candidate < *bound {
+                return candidate;
+            }
+        }
+    }
+
+    /// Check self for divisibility by SMALL_PRIMES.
+    fn trial_division(&self) -> TrialDivision {
+        if let Some(n) = self.to_u64() {
+            if n < 2 {
+                return TrialDivision::Composite;
+            }
+            if n < SMALL_PRIME_LIMIT as u64 && SMALL_PRIMES.contains(&(n as Limb)) {
+                return TrialDivision::SmallPrime;
+            }
+        }
+
+        if SMALL_PRIMES.iter().any(|p| self.divmod_limb(*p).1 == 0) {
+            TrialDivision::Composite
+        } else {
+            TrialDivision::Unknown
+        }
+    }
+
+    /// Returns true if self passes rounds of the Miller-Rabin test, with random bases from rng.
+    /// Trial division by small primes filters out most composites first.
+    ///
+    /// Primes always return true. Composites return true with probability at most 4 ^ -rounds.
+    pub fn is_probable_prime<R: Rng>(&self, rounds: usize, rng: &mut R) -> bool {
+        match self.trial_division() {
+            TrialDivision::SmallPrime => return true,
+            TrialDivision::Composite => return false,
+            TrialDivision::Unknown => {}
+        }
+
+        // self is odd and greater than SMALL_PRIME_LIMIT
+        let ctx = Montgomery::new(self).expect("self has no small factors, so it is odd");
+        let one = BigUint::one();
+        let n_minus_one = self - &one;
+
+        // n - 1 = d * 2 ^ s, with d odd
+        let mut s = 0;
+        while !n_minus_one.bit(s) {
+            s += 1;
+        }
+        let d = &n_minus_one >> s;
+
+        // Pick bases in [2, n - 2]
+        let base_range = self - BigUint::from(3);
+        'witness: for _ in 0..rounds {
+            let a = BigUint::random_below(&base_range, rng) + BigUint::from(2);
+            let mut x = ctx.modpow(&a, &d);
+            if x == one || x == n_minus_one {
+                continue;
+            }
+
+            for _ in 1..s {
+                x = ctx.mul_mod(&x, &x);
+                if x == n_minus_one {
+                    continue 'witness;
+                }
+            }
+
+            // a is a witness that self is composite
+            return false;
+        }
+
+        true
+    }
+
+    /// Returns true if self is probably prime, using MILLER_RABIN_ROUNDS rounds.
+    pub fn is_prime<R: Rng>(&self, rng: &mut R) -> bool {
+        self.is_probable_prime(MILLER_RABIN_ROUNDS, rng)
+    }
+
+    /// Returns the smallest probable prime greater than self.
+    pub fn next_prime<R: Rng>(&self, rng: &mut R) -> Self {
+        let two = BigUint::from(2);
+        if *self < two {
+            return two;
+        }
+
+        // Only check odd candidates
+        let mut candidate = self + BigUint::one();
+        if candidate.is_even() {
+            candidate = candidate + BigUint::one();
+        }
+        while !candidate.is_prime(rng) {
+            candidate = candidate + &two;
+        }
+
+        candidate
+    }
+
+    /// Returns a random probable prime with exactly bits significant bits.
+    ///
+    /// The top two bits are set, so the product of two of these primes has exactly 2 * bits bits.
+    ///
+    /// Panics if bits is less than 2.
+    pub fn random_prime<R: Rng>(bits: usize, rng: &mut R) -> Self {
+        assert!(
+            bits >= 2,
+            "Primes with the top two bits set have at least 2 bits"
+        );
+
+        let top_bits = BigUint::from(3) << (bits - 2);
+        loop {
+            // Setting the low bit is redundant for 2-bit primes, because 3 is the only candidate
+            let candidate = BigUint::random_bits(bits, rng) | &top_bits | &BigUint::one();
+            if candidate.is_prime(rng) {
+                return candidate;
+            }
+        }
+    }
+
+    /// Returns a random safe prime p with exactly bits significant bits, where (p - 1) / 2 is also
+    /// prime.
+    ///
+    /// Panics if bits is less than 3.
+    pub fn random_safe_prime<R: Rng>(bits: usize, rng: &mut R) -> Self {
+        assert!(
+            bits >= 3,
+            "Safe primes with the top two bits set have at least 3 bits"
+        );
+
+        loop {
+            let q = BigUint::random_prime(bits - 1, rng);
+            let p = (&q << 1) + BigUint::one();
+
+            // Cheaply reject most candidates before the slower primality test
+            if matches!(p.trial_division(), TrialDivision::Composite) {
+                continue;
+            }
+            if p.is_prime(rng) {
+                return p;
+            }
+        }
+    }
+
+    /// Returns true if self is a probable safe prime: self and (self - 1) / 2 are both probably
+    /// prime.
+    pub fn is_safe_prime<R: Rng>(&self, rng: &mut R) -> bool {
+        self.is_prime(rng) && !self.is_even() && (self >> 1).is_prime(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rng::ChaCha20Rng;
+    use crate::test_util::NIST_P_HEX;
+
+    fn n(v: u64) -> BigUint {
+        BigUint::from(v)
+    }
+
+    #[test]
+    fn small_primes() {
+        assert_eq!(SMALL_PRIMES[0], 2);
+        assert_eq!(SMALL_PRIMES[1], 3);
+        assert_eq!(SMALL_PRIMES[SMALL_PRIME_COUNT - 1], 997);
+
+        let mut rng = ChaCha20Rng::from_u64_seed(1);
+        let primes: std::vec::Vec<u64> = (0..1000).filter(|v| n(*v).is_prime(&mut rng)).collect();
+        assert_eq!(primes.len(), SMALL_PRIME_COUNT);
+        assert!(primes
+            .iter()
+            .zip(SMALL_PRIMES.iter())
+            .all(|(a, b)| *a == *b as u64));
+    }
+
+    #[test]
+    fn primes() {
+        let mut rng = ChaCha20Rng::from_u64_seed(2);
+        for p in [1009, 7919, 1_000_000_007, 18446744073709551557] {
+            assert!(n(p).is_prime(&mut rng), "{} is prime", p);
+        }
+
+        // Mersenne primes
+        for e in [61, 89, 107, 127, 521] {
+            let p = (BigUint::one() << e) - BigUint::one();
+            assert!(p.is_probable_prime(8, &mut rng), "2 ^ {} - 1 is prime", e);
+        }
+
+        // Large primes are slow to test in debug builds, so only do a few rounds
+        let p = BigUint::from_hex(NIST_P_HEX);
+        assert!(p.is_probable_prime(2, &mut rng));
+    }
+
+    #[test]
+    fn composites() {
+        let mut rng = ChaCha20Rng::from_u64_seed(3);
+        for c in [0, 1, 4, 1001, 1_000_000_007 * 998_244_353] {
+            assert!(!n(c).is_prime(&mut rng), "{} is composite", c);
+        }
+
+        // Carmichael numbers fool the Fermat test, but not Miller-Rabin
+        for c in [561, 1105, 1729, 41041, 825265, 321197185, 5394826801] {
+            assert!(!n(c).is_prime(&mut rng), "{} is composite", c);
+        }
+
+        // 2 ^ 67 - 1 = 193707721 * 761838257287
+        let m67 = (BigUint::one() << 67) - BigUint::one();
+        assert!(!m67.is_prime(&mut rng));
+
+        // A product of two large primes
+        let p = (BigUint::one() << 127) - BigUint::one();
+        let q = (BigUint::one() << 89) - BigUint::one();
+        assert!(!(p * q).is_prime(&mut rng));
+    }
+
+    #[test]
+    fn next_prime() {
+        let mut rng = ChaCha20Rng::from_u64_seed(4);
+        assert_eq!(n(0).next_prime(&mut rng), n(2));
+        assert_eq!(n(1).next_prime(&mut rng), n(2));
+        assert_eq!(n(2).next_prime(&mut rng), n(3));
+        assert_eq!(n(3).next_prime(&mut rng), n(5));
+        assert_eq!(n(100).next_prime(&mut rng), n(101));
+        assert_eq!(n(997).next_prime(&mut rng), n(1009));
+        assert_eq!(
+            (BigUint::one() << 64).next_prime(&mut rng),
+            (BigUint::one() << 64) + n(13)
+        );
+    }
+
+    #[test]
+    fn random_below() {
+        let mut rng = ChaCha20Rng::from_u64_seed(5);
+        let bound = n(1000);
+        for _ in 0..100 {
+            assert!(BigUint::random_below(&bound, &mut rng) < bound);
+        }
+        assert_eq!(BigUint::random_below(&n(1), &mut rng), n(0));
+        assert!(BigUint::random_bits(13, &mut rng).bits() <= 13);
+        assert_eq!(BigUint::random_bits(0, &mut rng), n(0));
+    }
+
+    #[test]
+    fn random_prime() {
+        let mut rng = ChaCha20Rng::from_u64_seed(6);
+        for bits in [2, 3, 8, 64, 128, 256] {
+            let p = BigUint::random_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert!(p.bit(bits - 2));
+            assert!(p.is_prime(&mut rng));
+        }
+    }
+
+    #[test]
+    fn random_prime_deterministic() {
+        let p1 = BigUint::random_prime(128, &mut ChaCha20Rng::from_u64_seed(7));
+        let p2 = BigUint::random_prime(128, &mut ChaCha20Rng::from_u64_seed(7));
+        assert_eq!(p1, p2);
+    }
+
+    #[test]
+    fn safe_prime() {
+        let mut rng = ChaCha20Rng::from_u64_seed(8);
+        for bits in [3, 16, 64] {
+            let p = BigUint::random_safe_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_safe_prime(&mut rng));
+        }
+
+        // The NIST prime is a safe prime, but is_safe_prime() is slow in debug builds
+        let p = BigUint::from_hex(NIST_P_HEX);
+        assert!(p.is_probable_prime(2, &mut rng));
+        assert!((p >> 1).is_probable_prime(2, &mut rng));
+        assert!(n(23).is_safe_prime(&mut rng));
+        assert!(!n(13).is_safe_prime(&mut rng));
+        assert!(!n(2).is_safe_prime(&mut rng));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 bits")]
+    fn invalid_random_prime_bits() {
+        BigUint::random_prime(1, &mut ChaCha20Rng::from_u64_seed(9));
+    }
+}
@@ -0,0 +1,438 @@
+//! Black-box oracles, and wrappers which account for oracle queries
+//!
+//! Attacks take oracles as arguments, so they can be run against any implementation. Wrapping an
+//! oracle counts its queries, enforces a query budget, records a transcript, or adds latency.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::{borrow::ToOwned, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use crate::aes::{Aes, AES_128_KEY_BYTES, AES_BLOCK_BYTES};
+use crate::cipher::{self, CipherError, Mode};
+use crate::padding::Padding;
+use crate::rng::{random_bytes, Rng};
+
+pub mod comments;
+pub mod ecb_cbc;
+pub mod ecb_suffix;
+pub mod padding;
+pub mod profile;
+
+/// A black-box oracle, which answers queries.
+pub trait Oracle {
+    /// The query type
+    type Input: ?Sized;
+    /// The answer type
+    type Output;
+
+    /// Returns the oracle's answer to input.
+    fn query(&mut self, input: &Self::Input) -> Self::Output;
+}
+
+impl<O: Oracle + ?Sized> Oracle for &mut O {
+    type Input = O::Input;
+    type Output = O::Output;
+
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        (**self).query(input)
+    }
+}
+
+/// An oracle which encrypts chosen plaintext, and returns the ciphertext.
+pub trait EncryptionOracle: Oracle<Input = [u8], Output = Vec<u8>> {}
+
+impl<O: Oracle<Input = [u8], Output = Vec<u8>> + ?Sized> EncryptionOracle for O {}
+
+/// An oracle which decrypts chosen ciphertext, and returns the plaintext, or an error.
+pub trait DecryptionOracle: Oracle<Input = [u8], Output = Result<Vec<u8>, CipherError>> {}
+
+impl<O> DecryptionOracle for O where
+    O: Oracle<Input = [u8], Output = Result<Vec<u8>, CipherError>> + ?Sized
+{
+}
+
+/// An oracle which decrypts an IV followed by ciphertext, and returns true if the plaintext has
+/// valid padding.
+pub trait PaddingOracle: Oracle<Input = [u8], Output = bool> {}
+
+impl<O: Oracle<Input = [u8], Output = bool> + ?Sized> PaddingOracle for O {}
+
+/// A message and its authentication tag
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MacQuery {
+    /// The message
+    pub message: Vec<u8>,
+    /// The claimed authentication tag for message
+    pub tag: Vec<u8>,
+}
+
+/// An oracle which returns true if the tag is valid for the message.
+pub trait MacOracle: Oracle<Input = MacQuery, Output = bool> {}
+
+impl<O: Oracle<Input = MacQuery, Output = bool> + ?Sized> MacOracle for O {}
+
+/// An oracle which calls a function on each byte query.
+#[derive(Clone, Copy, Debug)]
+pub struct FnOracle<F>(pub F);
+
+impl<F, T> Oracle for FnOracle<F>
+where
+    F: FnMut(&[u8]) -> T,
+{
+    type Input = [u8];
+    type Output = T;
+
+    fn query(&mut self, input: &[u8]) -> T {
+        (self.0)(input)
+    }
+}
+
+/// An oracle which counts the queries to an inner oracle.
+#[derive(Clone, Debug)]
+pub struct Counting<O> {
+    /// The inner oracle
+    inner: O,
+    /// The number of queries so far
+    queries: usize,
+}
+
+impl<O: Oracle> Counting<O> {
+    /// Wrap inner, with a zero query count.
+    pub fn new(inner: O) -> Self {
+        Counting { inner, queries: 0 }
+    }
+
+    /// Returns the number of queries so far.
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+
+    /// Returns a reference to the inner oracle.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner oracle. Calls through it are not counted.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Returns the inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: Oracle> Oracle for Counting<O> {
+    type Input = O::Input;
+    type Output = O::Output;
+
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        self.queries += 1;
+        self.inner.query(input)
+    }
+}
+
+/// The reasons an oracle query can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OracleError {
+    /// Every query in the budget has been used
+    BudgetExceeded {
+        /// The query budget
+        budget: usize,
+    },
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OracleError::BudgetExceeded { budget } => {
+                write!(f, "Oracle query budget of {} queries exceeded", budget)
+            }
+        }
+    }
+}
+
+/// An oracle which allows a limited number of queries to an inner oracle.
+#[derive(Clone, Debug)]
+pub struct Budgeted<O> {
+    /// The inner oracle
+    inner: O,
+    /// The query budget
+    budget: usize,
+    /// The number of queries so far
+    queries: usize,
+}
+
+impl<O: Oracle> Budgeted<O> {
+    /// Wrap inner, allowing budget queries.
+    pub fn new(inner: O, budget: usize) -> Self {
+        Budgeted {
+            inner,
+            budget,
+            queries: 0,
+        }
+    }
+
+    /// Returns the number of queries remaining in the budget.
+    pub fn remaining(&self) -> usize {
+        self.budget - self.queries
+    }
+
+    /// Returns the inner oracle's answer to input.
+    ///
+    /// Returns an error if the budget has been used, without querying the inner oracle.
+    pub fn try_query(&mut self, input: &O::Input) -> Result<O::Output, OracleError> {
+        if self.queries == self.budget {
+            return Err(OracleError::BudgetExceeded {
+                budget: self.budget,
+            });
+        }
+
+        self.queries += 1;
+        Ok(self.inner.query(input))
+    }
+}
+
+impl<O: Oracle> Oracle for Budgeted<O> {
+    type Input = O::Input;
+    type Output = O::Output;
+
+    /// Panics if the budget has been used. Use try_query() for a non-panicking version.
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        match self.try_query(input) {
+            Ok(output) => output,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// An oracle which records every query to an inner oracle, and its answer.
+pub struct Transcript<O: Oracle>
+where
+    O::Input: ToOwned,
+{
+    /// The inner oracle
+    inner: O,
+    /// The queries and answers so far, in order
+    entries: Vec<(<O::Input as ToOwned>::Owned, O::Output)>,
+}
+
+impl<O: Oracle> Transcript<O>
+where
+    O::Input: ToOwned,
+    O::Output: Clone,
+{
+    /// Wrap inner, with an empty transcript.
+    pub fn new(inner: O) -> Self {
+        Transcript {
+            inner,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the queries and answers so far, in order.
+    pub fn entries(&self) -> &[(<O::Input as ToOwned>::Owned, O::Output)] {
+        &self.entries
+    }
+}
+
+impl<O: Oracle> Oracle for Transcript<O>
+where
+    O::Input: ToOwned,
+    O::Output: Clone,
+{
+    type Input = O::Input;
+    type Output = O::Output;
+
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        let output = self.inner.query(input);
+        self.entries.push((input.to_owned(), output.clone()));
+        output
+    }
+}
+
+impl<O: Oracle> fmt::Debug for Transcript<O>
+where
+    O::Input: ToOwned,
+{
+    /// Formats the transcript length, without the inner oracle or entries.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transcript")
+            .field("len", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An oracle which sleeps before each query to an inner oracle.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct Delayed<O> {
+    /// The inner oracle
+    inner: O,
+    /// The time to sleep before each query
+    latency: Duration,
+}
+
+#[cfg(feature = "std")]
+impl<O: Oracle> Delayed<O> {
+    /// Wrap inner, sleeping for latency before each query.
+    pub fn new(inner: O, latency: Duration) -> Self {
+        Delayed { inner, latency }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O: Oracle> Oracle for Delayed<O> {
+    type Input = O::Input;
+    type Output = O::Output;
+
+    fn query(&mut self, input: &Self::Input) -> Self::Output {
+        std::thread::sleep(self.latency);
+        self.inner.query(input)
+    }
+}
+
+/// AES-128 in CBC mode, with PKCS#7 padding, a random key, and a random IV for each encryption.
+/// Encrypted data is the IV followed by the ciphertext. Shared by the CBC oracles.
+#[derive(Clone)]
+pub(crate) struct RandomIvCbc<R> {
+    /// The source of IVs
+    rng: R,
+    /// The cipher with the random key
+    aes: Aes,
+}
+
+impl<R: Rng> RandomIvCbc<R> {
+    /// Create a CBC cipher, using a random key and IVs from rng.
+    pub(crate) fn new(mut rng: R) -> Self {
+        let key = random_bytes(&mut rng, AES_128_KEY_BYTES);
+        RandomIvCbc {
+            rng,
+            aes: Aes::new(&key).expect("key is 16 bytes"),
+        }
+    }
+
+    /// Returns a random IV followed by plaintext encrypted with PKCS#7 padding.
+    pub(crate) fn encrypt(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let mut data = random_bytes(&mut self.rng, AES_BLOCK_BYTES);
+        let ciphertext =
+            cipher::encrypt_padded(&self.aes, Mode::Cbc, Padding::Pkcs7, &data, plaintext)
+                .expect("IV is one block");
+        data.extend_from_slice(&ciphertext);
+        data
+    }
+
+    /// Returns the plaintext decrypted from an IV followed by the ciphertext.
+    ///
+    /// Returns an error if the data is shorter than an IV, or can not be decrypted.
+    pub(crate) fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let iv_len = data.len().min(AES_BLOCK_BYTES);
+        let (iv, ciphertext) = data.split_at(iv_len);
+        cipher::decrypt_padded(&self.aes, Mode::Cbc, Padding::Pkcs7, iv, ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an ECB encryption oracle.
+    fn ecb_oracle() -> impl EncryptionOracle {
+        let aes = Aes::new(b"YELLOW SUBMARINE").expect("key is valid");
+        FnOracle(move |plaintext: &[u8]| {
+            cipher::encrypt_padded(&aes, Mode::Ecb, Padding::Pkcs7, &[], plaintext)
+                .expect("ECB has no IV")
+        })
+    }
+
+    /// Uses any encryption oracle, through the trait.
+    fn ciphertext_len<O: EncryptionOracle>(mut oracle: O, len: usize) -> usize {
+        oracle.query(&vec![0; len]).len()
+    }
+
+    /// A MAC oracle which accepts tags equal to the message.
+    struct EchoMac;
+
+    impl Oracle for EchoMac {
+        type Input = MacQuery;
+        type Output = bool;
+
+        fn query(&mut self, input: &MacQuery) -> bool {
+            input.message == input.tag
+        }
+    }
+
+    #[test]
+    fn counting() {
+        let mut oracle = Counting::new(ecb_oracle());
+        assert_eq!(oracle.queries(), 0);
+        assert_eq!(ciphertext_len(&mut oracle, 0), 16);
+        assert_eq!(ciphertext_len(&mut oracle, 16), 32);
+        assert_eq!(oracle.queries(), 2);
+    }
+
+    #[test]
+    fn budgeted() {
+        let mut oracle = Budgeted::new(Counting::new(ecb_oracle()), 2);
+        assert_eq!(oracle.remaining(), 2);
+        assert!(oracle.try_query(b"a").is_ok());
+        assert_eq!(oracle.query(b"b").len(), 16);
+        assert_eq!(oracle.remaining(), 0);
+        assert_eq!(
+            oracle.try_query(b"c"),
+            Err(OracleError::BudgetExceeded { budget: 2 })
+        );
+
+        // Failed queries are not passed to the inner oracle
+        assert_eq!(oracle.inner.queries(), 2);
+    }
+
+    #[test]
+    fn transcript() {
+        let mut oracle = Transcript::new(EchoMac);
+        let valid = MacQuery {
+            message: b"msg".to_vec(),
+            tag: b"msg".to_vec(),
+        };
+        let invalid = MacQuery {
+            message: b"msg".to_vec(),
+            tag: b"tag".to_vec(),
+        };
+        assert!(oracle.query(&valid));
+        assert!(!oracle.query(&invalid));
+        assert_eq!(oracle.entries(), [(valid, true), (invalid, false)]);
+        assert_eq!(format!("{:?}", oracle), "Transcript { len: 2, .. }");
+
+        let mut oracle = Transcript::new(FnOracle(|input: &[u8]| input.len().is_multiple_of(2)));
+        let padding_oracle: &mut dyn PaddingOracle = &mut oracle;
+        assert!(padding_oracle.query(b"ab"));
+        assert_eq!(oracle.entries(), [(b"ab".to_vec(), true)]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn delayed() {
+        let latency = Duration::from_millis(5);
+        let mut oracle = Counting::new(Delayed::new(ecb_oracle(), latency));
+
+        let start = std::time::Instant::now();
+        oracle.query(b"a");
+        oracle.query(b"b");
+        assert!(start.elapsed() >= 2 * latency);
+        assert_eq!(oracle.queries(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle query budget of 1 queries exceeded")]
+    fn invalid_budget_exceeded() {
+        let mut oracle = Budgeted::new(ecb_oracle(), 1);
+        oracle.query(b"a");
+        oracle.query(b"b");
+    }
+}
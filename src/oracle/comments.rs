@@ -0,0 +1,134 @@
+//! A comment service, which wraps quoted user data in a fixed prefix and suffix, then encrypts it
+//! using CBC mode
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::cipher::CipherError;
+use crate::oracle::{Oracle, RandomIvCbc};
+use crate::rng::Rng;
+
+/// The bytes added before the user data
+pub const COMMENT_PREFIX: &[u8] = b"comment1=cooking%20MCs;userdata=";
+/// The bytes added after the user data
+pub const COMMENT_SUFFIX: &[u8] = b";comment2=%20like%20a%20pound%20of%20bacon";
+/// The bytes which make a decrypted comment an admin comment
+pub const ADMIN_TOKEN: &[u8] = b";admin=true;";
+
+/// Returns user_data with ';' quoted as "%3B", and '=' quoted as "%3D".
+pub fn quote_user_data(user_data: &[u8]) -> Vec<u8> {
+    let mut quoted = Vec::with_capacity(user_data.len());
+    for &byte in user_data {
+        match byte {
+            b';' => quoted.extend_from_slice(b"%3B"),
+            b'=' => quoted.extend_from_slice(b"%3D"),
+            _ => quoted.push(byte),
+        }
+    }
+
+    quoted
+}
+
+/// An oracle which returns an encrypted comment containing user data.
+///
+/// Each query quotes the user data, adds COMMENT_PREFIX and COMMENT_SUFFIX, then encrypts it using
+/// AES-128 in CBC mode, with PKCS#7 padding. The key is chosen when the service is created, and
+/// each query uses a new random IV. Returns the IV followed by the ciphertext.
+#[derive(Clone)]
+pub struct CommentOracle<R> {
+    /// The cipher with the random key, and the source of IVs
+    cbc: RandomIvCbc<R>,
+}
+
+impl<R: Rng> CommentOracle<R> {
+    /// Create a comment service, using a random key and IVs from rng.
+    pub fn new(rng: R) -> Self {
+        CommentOracle {
+            cbc: RandomIvCbc::new(rng),
+        }
+    }
+
+    /// Returns the comment decrypted from an IV followed by the ciphertext.
+    ///
+    /// Returns an error if the data is shorter than an IV, or can not be decrypted.
+    pub fn decrypt_comment(&self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        self.cbc.decrypt(data)
+    }
+
+    /// Returns true if the comment decrypted from data contains ADMIN_TOKEN.
+    ///
+    /// Returns an error if the data is shorter than an IV, or can not be decrypted.
+    pub fn is_admin(&self, data: &[u8]) -> Result<bool, CipherError> {
+        let comment = self.decrypt_comment(data)?;
+        Ok(comment
+            .windows(ADMIN_TOKEN.len())
+            .any(|window| window == ADMIN_TOKEN))
+    }
+}
+
+impl<R: Rng> Oracle for CommentOracle<R> {
+    type Input = [u8];
+    type Output = Vec<u8>;
+
+    fn query(&mut self, user_data: &[u8]) -> Vec<u8> {
+        let comment = [COMMENT_PREFIX, &quote_user_data(user_data), COMMENT_SUFFIX].concat();
+        self.cbc.encrypt(&comment)
+    }
+}
+
+impl<R> fmt::Debug for CommentOracle<R> {
+    /// Formats the service without its key or random number generator.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CommentOracle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::AES_BLOCK_BYTES;
+    use crate::cipher::ModeError;
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote_user_data(b"a;b=c%"), b"a%3Bb%3Dc%");
+        assert_eq!(quote_user_data(b""), b"");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut oracle = CommentOracle::new(ChaCha20Rng::from_u64_seed(16));
+        let data = oracle.query(ADMIN_TOKEN);
+        let comment = oracle.decrypt_comment(&data).expect("comment is valid");
+        assert_eq!(
+            comment,
+            [
+                COMMENT_PREFIX,
+                b"%3Badmin%3Dtrue%3B".as_slice(),
+                COMMENT_SUFFIX
+            ]
+            .concat()
+        );
+        assert_eq!(oracle.is_admin(&data), Ok(false));
+
+        // Each query uses a new IV
+        assert_ne!(oracle.query(b""), oracle.query(b""));
+    }
+
+    #[test]
+    fn invalid() {
+        let oracle = CommentOracle::new(ChaCha20Rng::from_u64_seed(16));
+        assert_eq!(
+            oracle.decrypt_comment(&[0; 8]),
+            Err(CipherError::Mode(ModeError::IvLength {
+                len: 8,
+                expected: AES_BLOCK_BYTES
+            }))
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! An encryption oracle which randomly chooses between ECB and CBC modes
+
+#![deny(missing_docs)]
+
+use core::ops::RangeInclusive;
+
+use alloc::vec::Vec;
+
+use crate::aes::{Aes, AES_128_KEY_BYTES, AES_BLOCK_BYTES};
+use crate::cipher::{self, Mode};
+use crate::oracle::Oracle;
+use crate::padding::Padding;
+use crate::rng::{random_bytes, Rng};
+
+/// The possible lengths of the random bytes added before and after each plaintext
+pub const RANDOM_PAD_LENS: RangeInclusive<usize> = 5..=10;
+
+/// An AES-128 encryption oracle which uses a new random key for each query.
+///
+/// Each query adds random bytes before and after the chosen plaintext, then encrypts it using ECB
+/// or CBC mode with PKCS#7 padding. CBC mode uses a random IV. Each choice is equally likely.
+#[derive(Clone, Debug)]
+pub struct EcbCbcOracle<R> {
+    /// The source of keys, IVs, modes and padding bytes
+    rng: R,
+    /// The mode used for the most recent query
+    last_mode: Option<Mode>,
+}
+
+impl<R: Rng> EcbCbcOracle<R> {
+    /// Create an oracle which makes its random choices using rng.
+    pub fn new(rng: R) -> Self {
+        EcbCbcOracle {
+            rng,
+            last_mode: None,
+        }
+    }
+
+    /// Returns the mode used for the most recent query, or None if there have been no queries.
+    pub fn last_mode(&self) -> Option<Mode> {
+        self.last_mode
+    }
+
+    /// Returns a random number of random bytes, with a length in RANDOM_PAD_LENS.
+    fn random_pad(&mut self) -> Vec<u8> {
+        let lens = RANDOM_PAD_LENS.end() - RANDOM_PAD_LENS.start() + 1;
+        let len = RANDOM_PAD_LENS.start() + self.rng.next_below(lens as u64) as usize;
+        random_bytes(&mut self.rng, len)
+    }
+}
+
+impl<R: Rng> Oracle for EcbCbcOracle<R> {
+    type Input = [u8];
+    type Output = Vec<u8>;
+
+    fn query(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let key = random_bytes(&mut self.rng, AES_128_KEY_BYTES);
+        let aes = Aes::new(&key).expect("key is 16 bytes");
+        let mode = if self.rng.next_bool() {
+            Mode::Ecb
+        } else {
+            Mode::Cbc
+        };
+        let iv = random_bytes(&mut self.rng, mode.iv_len(AES_BLOCK_BYTES));
+
+        let prefix = self.random_pad();
+        let suffix = self.random_pad();
+        let data = [&prefix[..], plaintext, &suffix[..]].concat();
+
+        self.last_mode = Some(mode);
+        cipher::encrypt_padded(&aes, mode, Padding::Pkcs7, &iv, &data).expect("IV is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::attack::ecb::detect_oracle_ecb_or_cbc;
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn random_choices() {
+        let mut oracle = EcbCbcOracle::new(ChaCha20Rng::from_u64_seed(11));
+        assert_eq!(oracle.last_mode(), None);
+
+        let mut modes = Vec::new();
+        for _ in 0..100 {
+            let ciphertext = oracle.query(&[]);
+            let mode = oracle.last_mode().expect("oracle has been queried");
+
+            // The random bytes total 10 to 20 bytes, and the ciphertext does not include the IV
+            assert!([AES_BLOCK_BYTES, 2 * AES_BLOCK_BYTES].contains(&ciphertext.len()));
+            modes.push(mode);
+        }
+        assert!(modes.contains(&Mode::Ecb));
+        assert!(modes.contains(&Mode::Cbc));
+    }
+
+    #[test]
+    fn detection() {
+        let mut oracle = EcbCbcOracle::new(ChaCha20Rng::from_u64_seed(2));
+        for _ in 0..200 {
+            let detected = detect_oracle_ecb_or_cbc(&mut oracle, AES_BLOCK_BYTES);
+            assert_eq!(Some(detected), oracle.last_mode());
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! An ECB encryption oracle which appends a secret to each plaintext
+
+#![deny(missing_docs)]
+
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use alloc::vec::Vec;
+
+use crate::aes::{Aes, AES_128_KEY_BYTES};
+use crate::cipher::{self, Mode};
+use crate::oracle::Oracle;
+use crate::padding::Padding;
+use crate::rng::{random_bytes, Rng};
+
+/// The possible lengths of the random prefix, for oracles which have one
+pub const RANDOM_PREFIX_LENS: RangeInclusive<usize> = 0..=64;
+
+/// An AES-128 ECB encryption oracle, with a random key chosen when it is created.
+///
+/// Each query encrypts prefix || plaintext || secret, with PKCS#7 padding. The prefix is empty,
+/// or a fixed random prefix chosen when the oracle is created.
+#[derive(Clone)]
+pub struct EcbSuffixOracle {
+    /// The cipher with the random key
+    aes: Aes,
+    /// The bytes added before each plaintext
+    prefix: Vec<u8>,
+    /// The bytes added after each plaintext
+    secret: Vec<u8>,
+}
+
+impl EcbSuffixOracle {
+    /// Create an oracle which appends secret, using a random key from rng.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R, secret: &[u8]) -> Self {
+        EcbSuffixOracle::with_prefix(rng, &[], secret)
+    }
+
+    /// Create an oracle which adds a random prefix, and appends secret, using a random key from
+    /// rng. The prefix length is in RANDOM_PREFIX_LENS.
+    pub fn with_random_prefix<R: Rng + ?Sized>(rng: &mut R, secret: &[u8]) -> Self {
+        let lens = RANDOM_PREFIX_LENS.end() - RANDOM_PREFIX_LENS.start() + 1;
+        let len = RANDOM_PREFIX_LENS.start() + rng.next_below(lens as u64) as usize;
+        let prefix = random_bytes(rng, len);
+        EcbSuffixOracle::with_prefix(rng, &prefix, secret)
+    }
+
+    /// Create an oracle which adds prefix, and appends secret, using a random key from rng.
+    pub fn with_prefix<R: Rng + ?Sized>(rng: &mut R, prefix: &[u8], secret: &[u8]) -> Self {
+        let key = random_bytes(rng, AES_128_KEY_BYTES);
+        EcbSuffixOracle {
+            aes: Aes::new(&key).expect("key is 16 bytes"),
+            prefix: prefix.to_vec(),
+            secret: secret.to_vec(),
+        }
+    }
+
+    /// Returns the length of the prefix.
+    pub fn prefix_len(&self) -> usize {
+        self.prefix.len()
+    }
+}
+
+impl Oracle for EcbSuffixOracle {
+    type Input = [u8];
+    type Output = Vec<u8>;
+
+    fn query(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let data = [&self.prefix[..], plaintext, &self.secret[..]].concat();
+        cipher::encrypt_padded(&self.aes, Mode::Ecb, Padding::Pkcs7, &[], &data)
+            .expect("ECB has no IV")
+    }
+}
+
+impl fmt::Debug for EcbSuffixOracle {
+    /// Formats the oracle without its key or secret.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EcbSuffixOracle")
+            .field("prefix_len", &self.prefix.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::AES_BLOCK_BYTES;
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn suffix() {
+        let mut rng = ChaCha20Rng::from_u64_seed(12);
+        let mut oracle = EcbSuffixOracle::new(&mut rng, b"secret");
+        assert_eq!(oracle.prefix_len(), 0);
+
+        // "secret" and its padding, then a whole block of padding
+        assert_eq!(oracle.query(&[]).len(), AES_BLOCK_BYTES);
+        assert_eq!(oracle.query(&[0; 10]).len(), 2 * AES_BLOCK_BYTES);
+
+        // The key is fixed
+        assert_eq!(oracle.query(b"a"), oracle.query(b"a"));
+        assert_eq!(
+            format!("{:?}", oracle),
+            "EcbSuffixOracle { prefix_len: 0, .. }"
+        );
+    }
+
+    #[test]
+    fn random_prefix() {
+        let mut rng = ChaCha20Rng::from_u64_seed(14);
+        let mut lens = Vec::new();
+        for _ in 0..20 {
+            let mut oracle = EcbSuffixOracle::with_random_prefix(&mut rng, b"");
+            let len = oracle.prefix_len();
+            assert!(RANDOM_PREFIX_LENS.contains(&len));
+            assert_eq!(
+                oracle.query(&[]).len(),
+                (len + 1).next_multiple_of(AES_BLOCK_BYTES)
+            );
+            lens.push(len);
+        }
+        lens.dedup();
+        assert!(lens.len() > 1);
+    }
+}
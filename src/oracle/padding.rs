@@ -0,0 +1,85 @@
+//! A CBC padding oracle, which reveals whether decrypted data has valid PKCS#7 padding
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::cipher::CipherError;
+use crate::oracle::{Oracle, RandomIvCbc};
+use crate::rng::Rng;
+
+/// A padding oracle for AES-128 in CBC mode, with PKCS#7 padding.
+///
+/// The key is chosen when the oracle is created, and each encryption uses a new random IV.
+/// Encrypted data is the IV followed by the ciphertext. Each query decrypts data, and returns
+/// true if its padding is valid.
+#[derive(Clone)]
+pub struct CbcPaddingOracle<R> {
+    /// The cipher with the random key, and the source of IVs
+    cbc: RandomIvCbc<R>,
+}
+
+impl<R: Rng> CbcPaddingOracle<R> {
+    /// Create a padding oracle, using a random key and IVs from rng.
+    pub fn new(rng: R) -> Self {
+        CbcPaddingOracle {
+            cbc: RandomIvCbc::new(rng),
+        }
+    }
+
+    /// Returns a random IV followed by plaintext encrypted with PKCS#7 padding.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        self.cbc.encrypt(plaintext)
+    }
+
+    /// Returns the plaintext decrypted from an IV followed by the ciphertext.
+    ///
+    /// Returns an error if the data is shorter than an IV, or can not be decrypted.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+        self.cbc.decrypt(data)
+    }
+}
+
+impl<R: Rng> Oracle for CbcPaddingOracle<R> {
+    type Input = [u8];
+    type Output = bool;
+
+    /// Returns false for any decryption error, including invalid lengths.
+    fn query(&mut self, data: &[u8]) -> bool {
+        self.decrypt(data).is_ok()
+    }
+}
+
+impl<R> fmt::Debug for CbcPaddingOracle<R> {
+    /// Formats the oracle without its key or random number generator.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CbcPaddingOracle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::AES_BLOCK_BYTES;
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn padding_validity() {
+        let mut oracle = CbcPaddingOracle::new(ChaCha20Rng::from_u64_seed(17));
+        let mut data = oracle.encrypt(b"YELLOW SUBMARINE");
+        assert_eq!(data.len(), 3 * AES_BLOCK_BYTES);
+        assert_eq!(oracle.decrypt(&data), Ok(b"YELLOW SUBMARINE".to_vec()));
+        assert!(oracle.query(&data));
+
+        // The last plaintext block is all padding, so changing its last byte breaks it
+        data[2 * AES_BLOCK_BYTES - 1] ^= 1;
+        assert!(!oracle.query(&data));
+
+        assert!(!oracle.query(&data[..AES_BLOCK_BYTES]));
+        assert!(!oracle.query(&data[..20]));
+        assert!(!oracle.query(&[]));
+    }
+}
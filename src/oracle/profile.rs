@@ -0,0 +1,142 @@
+//! A user profile service, which encrypts profile cookies using ECB mode
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::aes::{Aes, AES_128_KEY_BYTES};
+use crate::cipher::{self, CipherError, Mode};
+use crate::cookie::{profile_for, Cookie, CookieError};
+use crate::oracle::Oracle;
+use crate::padding::Padding;
+use crate::rng::{random_bytes, Rng};
+
+/// The reasons a profile ciphertext can be rejected
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileError {
+    /// The ciphertext could not be decrypted
+    Cipher(CipherError),
+    /// The plaintext is not a valid cookie
+    Cookie(CookieError),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Cipher(e) => write!(f, "{}", e),
+            ProfileError::Cookie(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<CipherError> for ProfileError {
+    fn from(e: CipherError) -> Self {
+        ProfileError::Cipher(e)
+    }
+}
+
+impl From<CookieError> for ProfileError {
+    fn from(e: CookieError) -> Self {
+        ProfileError::Cookie(e)
+    }
+}
+
+/// An oracle which returns the encrypted profile for an email address.
+///
+/// Profiles are encoded using profile_for(), then encrypted using AES-128 in ECB mode, with
+/// PKCS#7 padding, and a random key chosen when the service is created.
+#[derive(Clone)]
+pub struct ProfileOracle {
+    /// The cipher with the random key
+    aes: Aes,
+}
+
+impl ProfileOracle {
+    /// Create a profile service, using a random key from rng.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let key = random_bytes(rng, AES_128_KEY_BYTES);
+        ProfileOracle {
+            aes: Aes::new(&key).expect("key is 16 bytes"),
+        }
+    }
+
+    /// Returns the profile decrypted from ciphertext.
+    ///
+    /// Returns an error if ciphertext can not be decrypted, or is not a valid cookie.
+    pub fn decrypt_profile(&self, ciphertext: &[u8]) -> Result<Cookie, ProfileError> {
+        let plaintext =
+            cipher::decrypt_padded(&self.aes, Mode::Ecb, Padding::Pkcs7, &[], ciphertext)?;
+        Ok(Cookie::parse_bytes(&plaintext)?)
+    }
+}
+
+impl Oracle for ProfileOracle {
+    type Input = str;
+    type Output = Vec<u8>;
+
+    fn query(&mut self, email: &str) -> Vec<u8> {
+        let encoded = profile_for(email).encode();
+        cipher::encrypt_padded(
+            &self.aes,
+            Mode::Ecb,
+            Padding::Pkcs7,
+            &[],
+            encoded.as_bytes(),
+        )
+        .expect("ECB has no IV")
+    }
+}
+
+impl fmt::Debug for ProfileOracle {
+    /// Formats the service without its key.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProfileOracle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::aes::AES_BLOCK_BYTES;
+    use crate::cookie::PROFILE_ROLE;
+    use crate::padding::PaddingError;
+    use crate::rng::ChaCha20Rng;
+
+    #[test]
+    fn round_trip() {
+        let mut oracle = ProfileOracle::new(&mut ChaCha20Rng::from_u64_seed(13));
+        let ciphertext = oracle.query("foo@bar.com");
+        assert_eq!(ciphertext.len(), 3 * AES_BLOCK_BYTES);
+
+        let profile = oracle
+            .decrypt_profile(&ciphertext)
+            .expect("profile is valid");
+        assert_eq!(profile, profile_for("foo@bar.com"));
+        assert_eq!(profile.get("role"), Some(PROFILE_ROLE));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut oracle = ProfileOracle::new(&mut ChaCha20Rng::from_u64_seed(13));
+        let ciphertext = oracle.query("foo@bar.com");
+
+        // Swapping the first block to the end breaks the padding
+        let mut swapped = ciphertext[AES_BLOCK_BYTES..].to_vec();
+        swapped.extend_from_slice(&ciphertext[..AES_BLOCK_BYTES]);
+        assert!(matches!(
+            oracle.decrypt_profile(&swapped),
+            Err(ProfileError::Cipher(CipherError::Padding(
+                PaddingError::InvalidPadByte { .. } | PaddingError::InvalidPadLength { .. }
+            )))
+        ));
+
+        // Removing the middle block leaves a valid cookie: "email=foo@bar.co" || "er"
+        let mut removed = ciphertext[..AES_BLOCK_BYTES].to_vec();
+        removed.extend_from_slice(&ciphertext[2 * AES_BLOCK_BYTES..]);
+        let profile = oracle.decrypt_profile(&removed).expect("profile is valid");
+        assert_eq!(profile.get("email"), Some("foo@bar.coer"));
+    }
+}
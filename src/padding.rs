@@ -0,0 +1,508 @@
+//! Block cipher padding schemes
+//!
+//! Every scheme except zero padding adds between 1 and block_size bytes, so padding can always be
+//! removed unambiguously. Unpadding checks the padding strictly, and does not allocate.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::rng::Rng;
+
+/// The largest block size supported by the padding schemes, in bytes.
+/// PKCS#7 stores the padding length in a single byte.
+pub const MAX_PADDING_BLOCK_BYTES: usize = 255;
+
+/// The ISO/IEC 7816-4 padding marker byte, which starts the padding
+pub const ISO_7816_4_MARKER: u8 = 0x80;
+
+/// A block cipher padding scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Padding {
+    /// PKCS#7 (RFC 5652): n bytes, each with the value n
+    Pkcs7,
+    /// ANSI X9.23: n - 1 zero bytes, then a byte with the value n
+    AnsiX923,
+    /// ISO/IEC 7816-4: a marker byte 0x80, then zero bytes
+    Iso7816_4,
+    /// ISO 10126: n - 1 random bytes, then a byte with the value n
+    Iso10126,
+    /// Zero bytes, only if the data is not a whole number of blocks.
+    /// Unpadding removes the trailing zero bytes in the final block, so data ending in zero is
+    /// ambiguous.
+    Zero,
+}
+
+/// The reasons padding or unpadding can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PaddingError {
+    /// The data is empty, or not a whole number of blocks
+    InvalidLength {
+        /// The length of the data
+        len: usize,
+        /// The block size
+        block_size: usize,
+    },
+    /// The final byte is zero, or longer than the block size
+    InvalidPadLength {
+        /// The padding length in the final byte
+        pad_len: usize,
+        /// The block size
+        block_size: usize,
+    },
+    /// A padding byte has the wrong value
+    InvalidPadByte {
+        /// The index of the byte in the data
+        index: usize,
+        /// The byte value
+        byte: u8,
+        /// The expected byte value
+        expected: u8,
+    },
+    /// The final block does not contain an ISO/IEC 7816-4 marker byte
+    MissingMarker,
+    /// The padding scheme needs random bytes, so data must be padded using pad_with()
+    NeedsRng,
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaddingError::InvalidLength { len, block_size } => write!(
+                f,
+                "Padded data must be a non-zero multiple of the {} byte block size, but it is {} bytes long",
+                block_size, len
+            ),
+            PaddingError::InvalidPadLength { pad_len, block_size } => write!(
+                f,
+                "Padding length must be between 1 and the {} byte block size, but it is {}",
+                block_size, pad_len
+            ),
+            PaddingError::InvalidPadByte {
+                index,
+                byte,
+                expected,
+            } => write!(
+                f,
+                "Padding byte {} must be {:#04x}, but it is {:#04x}",
+                index, expected, byte
+            ),
+            PaddingError::MissingMarker => write!(
+                f,
+                "Padding must contain a {:#04x} marker byte",
+                ISO_7816_4_MARKER
+            ),
+            PaddingError::NeedsRng => write!(f, "Padding needs random bytes, use pad_with()"),
+        }
+    }
+}
+
+/// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+fn check_block_size(block_size: usize) {
+    assert!(
+        block_size > 0 && block_size <= MAX_PADDING_BLOCK_BYTES,
+        "Padding block size must be between 1 and MAX_PADDING_BLOCK_BYTES"
+    );
+}
+
+/// Returns an error if data is empty, or not a whole number of blocks.
+fn check_padded_len(data: &[u8], block_size: usize) -> Result<(), PaddingError> {
+    if data.is_empty() || !data.len().is_multiple_of(block_size) {
+        return Err(PaddingError::InvalidLength {
+            len: data.len(),
+            block_size,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the padding length in the final byte of data, checking it is between 1 and
+/// block_size. Data must not be empty.
+fn final_pad_len(data: &[u8], block_size: usize) -> Result<usize, PaddingError> {
+    let pad_len = data[data.len() - 1] as usize;
+    if pad_len == 0 || pad_len > block_size {
+        return Err(PaddingError::InvalidPadLength {
+            pad_len,
+            block_size,
+        });
+    }
+
+    Ok(pad_len)
+}
+
+/// Returns an error if any byte in data[start..end] is not expected.
+fn check_pad_bytes(
+    data: &[u8],
+    start: usize,
+    end: usize,
+    expected: u8,
+) -> Result<(), PaddingError> {
+    for (index, &byte) in data.iter().enumerate().take(end).skip(start) {
+        if byte != expected {
+            return Err(PaddingError::InvalidPadByte {
+                index,
+                byte,
+                expected,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+impl Padding {
+    /// Every padding scheme
+    pub const ALL: [Padding; 5] = [
+        Padding::Pkcs7,
+        Padding::AnsiX923,
+        Padding::Iso7816_4,
+        Padding::Iso10126,
+        Padding::Zero,
+    ];
+
+    /// Returns the number of padding bytes added to len bytes of data, for block_size.
+    ///
+    /// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+    pub fn pad_len(self, len: usize, block_size: usize) -> usize {
+        check_block_size(block_size);
+
+        let pad_len = block_size - len % block_size;
+        match self {
+            Padding::Zero if pad_len == block_size => 0,
+            _ => pad_len,
+        }
+    }
+
+    /// Returns data padded to a whole number of blocks, using rng for any random padding bytes.
+    ///
+    /// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+    #[cfg(feature = "alloc")]
+    pub fn pad_with<R: Rng + ?Sized>(self, data: &[u8], block_size: usize, rng: &mut R) -> Vec<u8> {
+        let pad_len = self.pad_len(data.len(), block_size);
+
+        let mut v = Vec::with_capacity(data.len() + pad_len);
+        v.extend_from_slice(data);
+        match self {
+            Padding::Pkcs7 => v.resize(data.len() + pad_len, pad_len as u8),
+            Padding::AnsiX923 => {
+                v.resize(data.len() + pad_len - 1, 0);
+                v.push(pad_len as u8);
+            }
+            Padding::Iso7816_4 => {
+                v.push(ISO_7816_4_MARKER);
+                v.resize(data.len() + pad_len, 0);
+            }
+            Padding::Iso10126 => {
+                v.resize(data.len() + pad_len - 1, 0);
+                rng.fill_bytes(&mut v[data.len()..]);
+                v.push(pad_len as u8);
+            }
+            Padding::Zero => v.resize(data.len() + pad_len, 0),
+        }
+
+        v
+    }
+
+    /// Returns data padded to a whole number of blocks.
+    ///
+    /// Returns an error if self is Iso10126, which needs random bytes. Use pad_with() instead.
+    /// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+    #[cfg(feature = "alloc")]
+    pub fn pad(self, data: &[u8], block_size: usize) -> Result<Vec<u8>, PaddingError> {
+        if self == Padding::Iso10126 {
+            return Err(PaddingError::NeedsRng);
+        }
+
+        Ok(self.pad_with(data, block_size, &mut NoRng))
+    }
+
+    /// Returns data with its padding removed. Does not allocate.
+    ///
+    /// Returns an error if data is not a whole number of blocks, or its padding is invalid. Data
+    /// must not be empty, except for zero padding, which does not pad empty data.
+    /// ISO 10126 padding bytes are not checked, because they are random.
+    ///
+    /// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+    pub fn unpad(self, data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+        check_block_size(block_size);
+        if self == Padding::Zero && data.is_empty() {
+            return Ok(data);
+        }
+        check_padded_len(data, block_size)?;
+
+        let end = data.len() - 1;
+        let start = match self {
+            Padding::Pkcs7 => {
+                let pad_len = final_pad_len(data, block_size)?;
+                check_pad_bytes(data, data.len() - pad_len, end, pad_len as u8)?;
+                data.len() - pad_len
+            }
+            Padding::AnsiX923 => {
+                let pad_len = final_pad_len(data, block_size)?;
+                check_pad_bytes(data, data.len() - pad_len, end, 0)?;
+                data.len() - pad_len
+            }
+            Padding::Iso10126 => data.len() - final_pad_len(data, block_size)?,
+            Padding::Iso7816_4 => {
+                let final_block = data.len() - block_size;
+                let marker = data[final_block..]
+                    .iter()
+                    .rposition(|&b| b != 0)
+                    .map(|i| final_block + i)
+                    .ok_or(PaddingError::MissingMarker)?;
+                if data[marker] != ISO_7816_4_MARKER {
+                    return Err(PaddingError::MissingMarker);
+                }
+                marker
+            }
+            Padding::Zero => {
+                let final_block = data.len() - block_size;
+                data[final_block..]
+                    .iter()
+                    .rposition(|&b| b != 0)
+                    .map_or(final_block, |i| final_block + i + 1)
+            }
+        };
+
+        Ok(&data[..start])
+    }
+}
+
+/// A random number generator for padding schemes which do not use random bytes.
+#[cfg(feature = "alloc")]
+struct NoRng;
+
+#[cfg(feature = "alloc")]
+impl Rng for NoRng {
+    fn fill_bytes(&mut self, _dest: &mut [u8]) {
+        unreachable!("Deterministic padding schemes do not use random bytes");
+    }
+}
+
+/// Returns data padded to a whole number of blocks using PKCS#7.
+///
+/// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+#[cfg(feature = "alloc")]
+pub fn pkcs7_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    Padding::Pkcs7.pad_with(data, block_size, &mut NoRng)
+}
+
+/// Returns data with its PKCS#7 padding removed. Does not allocate.
+///
+/// Returns an error if data is not a whole number of blocks, or its padding is invalid.
+/// Panics if block_size is zero, or larger than MAX_PADDING_BLOCK_BYTES.
+pub fn pkcs7_unpad(data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+    Padding::Pkcs7.unpad(data, block_size)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    /// A deterministic source of bytes
+    struct CountingRng(u8);
+
+    impl Rng for CountingRng {
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for d in dest.iter_mut() {
+                self.0 = self.0.wrapping_add(1);
+                *d = self.0;
+            }
+        }
+    }
+
+    #[test]
+    fn pkcs7() {
+        assert_eq!(
+            pkcs7_pad(b"YELLOW SUBMARINE", 20),
+            b"YELLOW SUBMARINE\x04\x04\x04\x04"
+        );
+        assert_eq!(pkcs7_pad(b"", 4), [4, 4, 4, 4]);
+        assert_eq!(pkcs7_pad(b"abcd", 4), b"abcd\x04\x04\x04\x04");
+        assert_eq!(pkcs7_pad(b"abc", 1), b"abc\x01");
+
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16),
+            Ok(&b"ICE ICE BABY"[..])
+        );
+        assert_eq!(pkcs7_unpad(b"abcd\x04\x04\x04\x04", 4), Ok(&b"abcd"[..]));
+    }
+
+    #[test]
+    fn pkcs7_invalid() {
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16),
+            Err(PaddingError::InvalidPadByte {
+                index: 11,
+                byte: b'Y',
+                expected: 5
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16),
+            Err(PaddingError::InvalidPadByte {
+                index: 12,
+                byte: 1,
+                expected: 4
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x00", 16),
+            Err(PaddingError::InvalidPadLength {
+                pad_len: 0,
+                block_size: 16
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad(b"abc\x05", 4),
+            Err(PaddingError::InvalidPadLength {
+                pad_len: 5,
+                block_size: 4
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad(b"", 4),
+            Err(PaddingError::InvalidLength {
+                len: 0,
+                block_size: 4
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad(b"abc\x01\x01", 4),
+            Err(PaddingError::InvalidLength {
+                len: 5,
+                block_size: 4
+            })
+        );
+    }
+
+    #[test]
+    fn schemes() {
+        assert_eq!(
+            Padding::AnsiX923.pad(b"abc", 8),
+            Ok(b"abc\x00\x00\x00\x00\x05".to_vec())
+        );
+        assert_eq!(
+            Padding::Iso7816_4.pad(b"abc", 8),
+            Ok(b"abc\x80\x00\x00\x00\x00".to_vec())
+        );
+        assert_eq!(
+            Padding::Iso7816_4.pad(b"abcdefg", 8),
+            Ok(b"abcdefg\x80".to_vec())
+        );
+        assert_eq!(
+            Padding::Iso10126.pad_with(b"abc", 8, &mut CountingRng(0)),
+            b"abc\x01\x02\x03\x04\x05"
+        );
+        assert_eq!(
+            Padding::Zero.pad(b"abc", 8),
+            Ok(b"abc\x00\x00\x00\x00\x00".to_vec())
+        );
+        assert_eq!(Padding::Zero.pad(b"abcdefgh", 8), Ok(b"abcdefgh".to_vec()));
+        assert_eq!(Padding::Zero.pad_len(16, 8), 0);
+        assert_eq!(
+            Padding::Iso10126.pad(b"abc", 8),
+            Err(PaddingError::NeedsRng)
+        );
+        assert_eq!(Padding::Pkcs7.pad_len(16, 8), 8);
+    }
+
+    #[test]
+    fn round_trip() {
+        for padding in Padding::ALL {
+            for block_size in [1, 8, 16, MAX_PADDING_BLOCK_BYTES] {
+                for len in 0..(2 * block_size + 1).min(40) {
+                    // Zero padding can not remove data ending in zero
+                    let data: Vec<u8> = (1..=len as u8).collect();
+                    let padded = padding.pad_with(&data, block_size, &mut CountingRng(7));
+                    assert!(padded.len().is_multiple_of(block_size));
+                    assert_eq!(padded.len(), len + padding.pad_len(len, block_size));
+
+                    assert_eq!(
+                        padding.unpad(&padded, block_size),
+                        Ok(&data[..]),
+                        "{:?}",
+                        padding
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn schemes_invalid() {
+        assert_eq!(
+            Padding::AnsiX923.unpad(b"abc\x04\x00\x00\x00\x05", 8),
+            Err(PaddingError::InvalidPadByte {
+                index: 3,
+                byte: 4,
+                expected: 0
+            })
+        );
+        assert_eq!(
+            Padding::Iso7816_4.unpad(b"abc\x00\x00\x00\x00\x00", 8),
+            Err(PaddingError::MissingMarker)
+        );
+        assert_eq!(
+            Padding::Iso7816_4.unpad(&[0; 8], 8),
+            Err(PaddingError::MissingMarker)
+        );
+        assert_eq!(
+            Padding::Iso7816_4.unpad(b"abcdefgh", 8),
+            Err(PaddingError::MissingMarker)
+        );
+        // The marker must be in the final block
+        assert_eq!(
+            Padding::Iso7816_4.unpad(b"abcdefg\x80\x00\x00\x00\x00", 4),
+            Err(PaddingError::MissingMarker)
+        );
+        assert_eq!(
+            Padding::Iso10126.unpad(b"abc\x09\x09\x09\x09\x09", 8),
+            Err(PaddingError::InvalidPadLength {
+                pad_len: 9,
+                block_size: 8
+            })
+        );
+        // Zero padding only fails on length
+        assert_eq!(Padding::Zero.unpad(&[0; 8], 8), Ok(&[][..]));
+        assert_eq!(Padding::Zero.unpad(&[], 8), Ok(&[][..]));
+        assert!(Padding::Pkcs7.unpad(&[], 8).is_err());
+        assert!(Padding::Zero.unpad(&[0; 7], 8).is_err());
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            format!(
+                "{}",
+                PaddingError::InvalidPadByte {
+                    index: 3,
+                    byte: 4,
+                    expected: 0
+                }
+            ),
+            "Padding byte 3 must be 0x00, but it is 0x04"
+        );
+        assert_eq!(
+            format!("{}", PaddingError::MissingMarker),
+            "Padding must contain a 0x80 marker byte"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Padding block size must be between 1 and MAX_PADDING_BLOCK_BYTES")]
+    fn invalid_block_size_too_large() {
+        pkcs7_pad(b"abc", 256);
+    }
+
+    #[test]
+    #[should_panic(expected = "Padding block size must be between 1 and MAX_PADDING_BLOCK_BYTES")]
+    fn invalid_block_size_zero() {
+        pkcs7_unpad(b"abc", 0).ok();
+    }
+}
@@ -0,0 +1,348 @@
+//! Random number generation
+//!
+//! OsRng reads entropy from the operating system. ChaCha20Rng is a seedable deterministic
+//! generator, so a failing run can be reproduced from its printed seed.
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+/// A source of random bytes.
+///
+/// Functions that need randomness take a source as an argument, so tests can use a deterministic
+/// source.
+pub trait Rng {
+    /// Fill dest with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+
+    /// Returns a random u32.
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Returns a random u64.
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Returns a uniformly random u64 less than bound.
+    ///
+    /// Panics if bound is zero.
+    fn next_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Random number bound must not be zero");
+
+        // Reject the final partial range of values, so the result is unbiased
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let r = self.next_u64();
+            if r < limit {
+                return r % bound;
+            }
+        }
+    }
+
+    /// Returns a random bool.
+    fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 1
+    }
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        (**self).fill_bytes(dest)
+    }
+}
+
+/// Returns len random bytes from rng.
+#[cfg(feature = "alloc")]
+pub fn random_bytes<R: Rng + ?Sized>(rng: &mut R, len: usize) -> Vec<u8> {
+    let mut v = vec![0; len];
+    rng.fill_bytes(&mut v);
+    v
+}
+
+/// The path of the operating system entropy device
+#[cfg(feature = "std")]
+pub const OS_ENTROPY_PATH: &str = "/dev/urandom";
+
+/// A random number generator which reads entropy from the operating system.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsRng;
+
+#[cfg(feature = "std")]
+impl Rng for OsRng {
+    /// Panics if OS_ENTROPY_PATH can not be read.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        use std::fs::File;
+        use std::io::Read;
+
+        File::open(OS_ENTROPY_PATH)
+            .and_then(|mut f| f.read_exact(dest))
+            .expect("Reading OS entropy failed");
+    }
+}
+
+/// The number of bytes in a ChaCha20 key, which is used as the seed
+pub const CHACHA20_SEED_BYTES: usize = 32;
+/// The number of bytes in a ChaCha20 nonce
+pub const CHACHA20_NONCE_BYTES: usize = 12;
+/// The number of bytes in a ChaCha20 keystream block
+pub const CHACHA20_BLOCK_BYTES: usize = 64;
+
+/// The number of 32-bit words in the ChaCha20 state
+const CHACHA20_WORDS: usize = 16;
+/// The number of ChaCha20 double rounds
+const CHACHA20_DOUBLE_ROUNDS: usize = 10;
+/// The ChaCha20 constant words: "expand 32-byte k"
+const CHACHA20_CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// Apply the ChaCha quarter round to words a, b, c, and d of state.
+fn quarter_round(state: &mut [u32; CHACHA20_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// Returns the ChaCha20 keystream block for key, counter, and nonce, from RFC 8439.
+pub fn chacha20_block(
+    key: &[u8; CHACHA20_SEED_BYTES],
+    counter: u32,
+    nonce: &[u8; CHACHA20_NONCE_BYTES],
+) -> [u8; CHACHA20_BLOCK_BYTES] {
+    let mut input = [0u32; CHACHA20_WORDS];
+    input[..4].copy_from_slice(&CHACHA20_CONSTANTS);
+    for (word, bytes) in input[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    input[12] = counter;
+    for (word, bytes) in input[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let mut state = input;
+    for _ in 0..CHACHA20_DOUBLE_ROUNDS {
+        // Columns
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        // Diagonals
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0; CHACHA20_BLOCK_BYTES];
+    for ((bytes, s), i) in block.chunks_exact_mut(4).zip(&state).zip(&input) {
+        bytes.copy_from_slice(&s.wrapping_add(*i).to_le_bytes());
+    }
+
+    block
+}
+
+/// A deterministic cryptographically secure random number generator, using the ChaCha20
+/// keystream for a seed.
+///
+/// The seed is the ChaCha20 key. The 64-bit block counter uses the counter word and the first
+/// nonce word, and the rest of the nonce is zero.
+#[derive(Clone)]
+pub struct ChaCha20Rng {
+    /// The seed, used as the ChaCha20 key
+    seed: [u8; CHACHA20_SEED_BYTES],
+    /// The index of the next keystream block
+    block_counter: u64,
+    /// The current keystream block
+    block: [u8; CHACHA20_BLOCK_BYTES],
+    /// The index of the next unused byte in block
+    offset: usize,
+}
+
+impl ChaCha20Rng {
+    /// Create a generator from seed.
+    pub fn from_seed(seed: [u8; CHACHA20_SEED_BYTES]) -> Self {
+        ChaCha20Rng {
+            seed,
+            block_counter: 0,
+            block: [0; CHACHA20_BLOCK_BYTES],
+            offset: CHACHA20_BLOCK_BYTES,
+        }
+    }
+
+    /// Create a generator from a short seed, which is easy to print and type.
+    /// The seed is little-endian, followed by zero bytes.
+    pub fn from_u64_seed(seed: u64) -> Self {
+        let mut bytes = [0; CHACHA20_SEED_BYTES];
+        bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        ChaCha20Rng::from_seed(bytes)
+    }
+
+    /// Create a generator with a random seed from the operating system.
+    ///
+    /// Panics if OS entropy can not be read.
+    #[cfg(feature = "std")]
+    pub fn from_os_entropy() -> Self {
+        let mut seed = [0; CHACHA20_SEED_BYTES];
+        OsRng.fill_bytes(&mut seed);
+        ChaCha20Rng::from_seed(seed)
+    }
+
+    /// Returns the seed, which recreates this generator's output using from_seed().
+    pub fn seed(&self) -> [u8; CHACHA20_SEED_BYTES] {
+        self.seed
+    }
+
+    /// Generate the next keystream block.
+    fn refill(&mut self) {
+        let counter = self.block_counter as u32;
+        let mut nonce = [0; CHACHA20_NONCE_BYTES];
+        nonce[..4].copy_from_slice(&((self.block_counter >> 32) as u32).to_le_bytes());
+
+        self.block = chacha20_block(&self.seed, counter, &nonce);
+        self.block_counter = self.block_counter.wrapping_add(1);
+        self.offset = 0;
+    }
+}
+
+impl Rng for ChaCha20Rng {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut filled = 0;
+        while filled < dest.len() {
+            if self.offset == CHACHA20_BLOCK_BYTES {
+                self.refill();
+            }
+
+            let len = (dest.len() - filled).min(CHACHA20_BLOCK_BYTES - self.offset);
+            dest[filled..filled + len].copy_from_slice(&self.block[self.offset..self.offset + len]);
+            filled += len;
+            self.offset += len;
+        }
+    }
+}
+
+impl fmt::Debug for ChaCha20Rng {
+    /// Formats the generator without its seed or keystream.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChaCha20Rng")
+            .field("block_counter", &self.block_counter)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use crate::hex::{hex_decode, hex_encode};
+
+    /// The first two ChaCha20 keystream blocks for a zero key and nonce, from RFC 8439 A.1
+    const ZERO_KEYSTREAM: &str = "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee65869f07e7be5551387a98ba977c732d080dcb0f29a048e3656912c6533e32ee7aed29b721769ce64e43d57133b074d839d531ed1f28510afb45ace10a1f4b794d6f";
+
+    #[test]
+    fn chacha20_rfc_8439() {
+        // RFC 8439 section 2.3.2
+        let key: [u8; CHACHA20_SEED_BYTES] = core::array::from_fn(|i| i as u8);
+        let nonce = [0, 0, 0, 9, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        assert_eq!(
+            hex_encode(&chacha20_block(&key, 1, &nonce)),
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4ed2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+        );
+    }
+
+    #[test]
+    fn chacha20_rng_keystream() {
+        let mut rng = ChaCha20Rng::from_seed([0; CHACHA20_SEED_BYTES]);
+        assert_eq!(random_bytes(&mut rng, 128), hex_decode(ZERO_KEYSTREAM));
+
+        // Reads which cross block boundaries produce the same stream
+        let mut rng = ChaCha20Rng::from_u64_seed(0);
+        let mut split = Vec::new();
+        for len in [1, 62, 3, 0, 62] {
+            split.extend(random_bytes(&mut rng, len));
+        }
+        assert_eq!(split, hex_decode(ZERO_KEYSTREAM));
+    }
+
+    #[test]
+    fn chacha20_rng_reproducible() {
+        let mut first = ChaCha20Rng::from_u64_seed(1234);
+        let mut second = ChaCha20Rng::from_seed(first.seed());
+        assert_eq!(
+            random_bytes(&mut first, 100),
+            random_bytes(&mut second, 100)
+        );
+        assert_eq!(first.next_u64(), second.next_u64());
+
+        let mut other = ChaCha20Rng::from_u64_seed(1235);
+        assert_ne!(
+            random_bytes(&mut ChaCha20Rng::from_u64_seed(1234), 32),
+            random_bytes(&mut other, 32)
+        );
+        assert_eq!(
+            format!("{:?}", ChaCha20Rng::from_u64_seed(0)),
+            "ChaCha20Rng { block_counter: 0, .. }"
+        );
+    }
+
+    #[test]
+    fn chacha20_rng_counter_carry() {
+        let seed = [7; CHACHA20_SEED_BYTES];
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        rng.block_counter = u32::MAX as u64;
+
+        let mut expected = chacha20_block(&seed, u32::MAX, &[0; CHACHA20_NONCE_BYTES]).to_vec();
+        expected.extend(chacha20_block(
+            &seed,
+            0,
+            &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        ));
+        assert_eq!(random_bytes(&mut rng, 2 * CHACHA20_BLOCK_BYTES), expected);
+    }
+
+    #[test]
+    fn below() {
+        let mut rng = ChaCha20Rng::from_u64_seed(5);
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            let r = rng.next_below(6);
+            assert!(r < 6);
+            seen[r as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(rng.next_below(1), 0);
+
+        let trues = (0..200).filter(|_| rng.next_bool()).count();
+        assert!(trues > 50 && trues < 150);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn os_rng() {
+        let a = random_bytes(&mut OsRng, 32);
+        let b = random_bytes(&mut OsRng, 32);
+        assert_ne!(a, b);
+
+        let mut rng = ChaCha20Rng::from_os_entropy();
+        assert_ne!(rng.seed(), [0; CHACHA20_SEED_BYTES]);
+        assert_eq!(random_bytes(&mut rng, 0), []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Random number bound must not be zero")]
+    fn invalid_below_zero() {
+        ChaCha20Rng::from_u64_seed(0).next_below(0);
+    }
+}
@@ -2,25 +2,19 @@
 
 #![deny(missing_docs)]
 
-// I don't want to create duplicate modules here. But I can't get "crate" to work.
-#[path = "base64.rs"]
-#[allow(dead_code)]
-mod base64;
-#[path = "hex.rs"]
-#[allow(dead_code)]
-mod hex;
+use matasano_rust::{base64, hex};
 
 /// Input hex test vector for 1.1
-const HEX_TEST: &'static str =
+const HEX_TEST: &str =
     "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d";
 /// Expected output Base64 test vector for 1.1
-const B64_EXPECTED_TEST_OUTPUT: &'static str =
+const B64_EXPECTED_TEST_OUTPUT: &str =
     "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t";
 
 /// Check the test vectors for 1.1
 pub fn check() {
     println!("Hex test: '{}'", HEX_TEST);
-    let bytes_test = hex::hex_decode(&HEX_TEST);
+    let bytes_test = hex::hex_decode(HEX_TEST);
     println!("Hex decoded test: '{:?}'", bytes_test);
     let b64_test = base64::base64_encode(&bytes_test);
     println!("Base64 encoded test: '{}'", b64_test);
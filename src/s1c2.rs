@@ -0,0 +1,24 @@
+//! Set 1, Challenge 2, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::{hex, xor};
+
+/// Input hex test vector for 1.2
+const HEX_TEST: &str = "1c0111001f010100061a024b53535009181c";
+/// Hex key to XOR with the input for 1.2
+const HEX_KEY: &str = "686974207468652062756c6c277320657965";
+/// Expected output hex test vector for 1.2
+const HEX_EXPECTED_TEST_OUTPUT: &str = "746865206b696420646f6e277420706c6179";
+
+/// Check the test vectors for 1.2
+pub fn check() {
+    println!("Hex test: '{}'", HEX_TEST);
+    println!("Hex key: '{}'", HEX_KEY);
+    let bytes_test = hex::hex_decode(HEX_TEST);
+    let bytes_key = hex::hex_decode(HEX_KEY);
+    let bytes_output = xor::fixed_xor(&bytes_test, &bytes_key).expect("inputs are the same length");
+    println!("XOR output: '{}'", hex::HexDisplay::new(&bytes_output));
+    println!("Hex expected output: '{}'", HEX_EXPECTED_TEST_OUTPUT);
+    assert!(hex::hex_encode(&bytes_output) == HEX_EXPECTED_TEST_OUTPUT);
+}
@@ -0,0 +1,36 @@
+//! Set 1, Challenge 3, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::attack::xor::break_single_byte_xor_hex;
+use matasano_rust::score::Scorer;
+use matasano_rust::utf8;
+
+/// Input hex ciphertext for 1.3
+const HEX_CIPHERTEXT: &str = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
+/// The number of candidate keys to print
+const CANDIDATE_COUNT: usize = 3;
+/// Expected key for 1.3
+const EXPECTED_KEY: u8 = b'X';
+/// Expected plaintext for 1.3
+const EXPECTED_PLAINTEXT: &str = "Cooking MC's like a pound of bacon";
+
+/// Check the test vectors for 1.3
+pub fn check() {
+    println!("Hex ciphertext: '{}'", HEX_CIPHERTEXT);
+    let ranked = break_single_byte_xor_hex(HEX_CIPHERTEXT, Scorer::default(), CANDIDATE_COUNT);
+    for candidate in &ranked {
+        println!(
+            "Key: {:#04x}, score: {:.3}, plaintext: {:?}",
+            candidate.key,
+            candidate.score,
+            String::from_utf8_lossy(&candidate.plaintext)
+        );
+    }
+
+    let best = &ranked[0];
+    println!("Expected key: {:#04x}", EXPECTED_KEY);
+    println!("Expected plaintext: '{}'", EXPECTED_PLAINTEXT);
+    assert!(best.key == EXPECTED_KEY);
+    assert!(utf8::utf8_decode(&best.plaintext) == EXPECTED_PLAINTEXT);
+}
@@ -0,0 +1,41 @@
+//! Set 1, Challenge 4, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::attack::xor::scan_single_byte_xor_hex_lines;
+use matasano_rust::score::Scorer;
+use matasano_rust::utf8;
+
+use crate::data::read_data_file;
+
+/// Data file for 1.4, containing one hex ciphertext per line
+const DATA_FILE: &str = "4.txt";
+/// The number of candidate lines to print
+const CANDIDATE_COUNT: usize = 3;
+/// Expected key for 1.4
+const EXPECTED_KEY: u8 = 0x35;
+/// Expected plaintext for 1.4
+const EXPECTED_PLAINTEXT: &str = "Now that the party is jumping\n";
+
+/// Check the data file for 1.4
+pub fn check() {
+    let text = read_data_file(DATA_FILE);
+
+    let ranked = scan_single_byte_xor_hex_lines(&text, Scorer::default(), CANDIDATE_COUNT);
+    for candidate in &ranked {
+        let (line, key) = candidate.key;
+        println!(
+            "Line: {}, key: {:#04x}, score: {:.3}, plaintext: {:?}",
+            line,
+            key,
+            candidate.score,
+            String::from_utf8_lossy(&candidate.plaintext)
+        );
+    }
+
+    let best = &ranked[0];
+    println!("Expected key: {:#04x}", EXPECTED_KEY);
+    println!("Expected plaintext: {:?}", EXPECTED_PLAINTEXT);
+    assert!(best.key.1 == EXPECTED_KEY);
+    assert!(utf8::utf8_decode(&best.plaintext) == EXPECTED_PLAINTEXT);
+}
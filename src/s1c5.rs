@@ -0,0 +1,28 @@
+//! Set 1, Challenge 5, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::{hex, utf8, xor};
+
+/// Input plaintext test vector for 1.5
+const PLAINTEXT_TEST: &str =
+    "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+/// Repeating XOR key for 1.5
+const KEY: &str = "ICE";
+/// Expected output hex test vector for 1.5
+const HEX_EXPECTED_TEST_OUTPUT: &str = "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f";
+
+/// Check the test vectors for 1.5
+pub fn check() {
+    println!("Plaintext test: '{}'", PLAINTEXT_TEST);
+    println!("Key: '{}'", KEY);
+    let bytes_test = utf8::utf8_encode(PLAINTEXT_TEST);
+    let bytes_key = utf8::utf8_encode(KEY);
+    let bytes_output = xor::repeating_key_xor(&bytes_test, &bytes_key);
+    println!("XOR output: '{}'", hex::HexDisplay::new(&bytes_output));
+    println!("Hex expected output: '{}'", HEX_EXPECTED_TEST_OUTPUT);
+    assert!(hex::hex_encode(&bytes_output) == HEX_EXPECTED_TEST_OUTPUT);
+
+    // The expected output must also decode to the same bytes
+    assert!(hex::hex_decode(HEX_EXPECTED_TEST_OUTPUT) == bytes_output);
+}
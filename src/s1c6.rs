@@ -0,0 +1,55 @@
+//! Set 1, Challenge 6, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::attack::xor::{break_repeating_key_xor, KeysizeMethod};
+use matasano_rust::score::Scorer;
+use matasano_rust::{base64, math, utf8};
+
+use crate::data::read_data_file;
+
+/// Hamming distance test vectors for 1.6
+const HAMMING_LEFT_TEST: &str = "this is a test";
+/// Hamming distance test vectors for 1.6
+const HAMMING_RIGHT_TEST: &str = "wokka wokka!!!";
+/// Expected Hamming distance for the test vectors
+const HAMMING_EXPECTED_TEST_OUTPUT: usize = 37;
+
+/// Data file for 1.6, containing wrapped Base64 ciphertext
+const DATA_FILE: &str = "6.txt";
+/// Expected key for 1.6
+const EXPECTED_KEY: &str = "Terminator X: Bring the noise";
+/// Expected start of the plaintext for 1.6
+const EXPECTED_PLAINTEXT_START: &str = "I'm back and I'm ringin' the bell";
+
+/// Check the test vectors and data file for 1.6
+pub fn check() {
+    let distance = math::hamming_distance(
+        &utf8::utf8_encode(HAMMING_LEFT_TEST),
+        &utf8::utf8_encode(HAMMING_RIGHT_TEST),
+    );
+    println!(
+        "Hamming distance between '{}' and '{}': {}",
+        HAMMING_LEFT_TEST, HAMMING_RIGHT_TEST, distance
+    );
+    println!("Expected distance: {}", HAMMING_EXPECTED_TEST_OUTPUT);
+    assert!(distance == HAMMING_EXPECTED_TEST_OUTPUT);
+
+    let text = read_data_file(DATA_FILE);
+    let ciphertext = base64::base64_decode_wrapped(&text);
+
+    for method in [
+        KeysizeMethod::HammingDistance,
+        KeysizeMethod::IndexOfCoincidence,
+    ] {
+        let ranked = break_repeating_key_xor(&ciphertext, method, Scorer::default(), 1);
+        let best = &ranked[0];
+        let key = utf8::utf8_decode(&best.key);
+        let plaintext = utf8::utf8_decode(&best.plaintext);
+        println!("{:?} key: '{}'", method, key);
+        println!("Plaintext:\n{}", plaintext);
+        println!("Expected key: '{}'", EXPECTED_KEY);
+        assert!(key == EXPECTED_KEY);
+        assert!(plaintext.starts_with(EXPECTED_PLAINTEXT_START));
+    }
+}
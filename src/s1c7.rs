@@ -0,0 +1,32 @@
+//! Set 1, Challenge 7, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::Aes;
+use matasano_rust::cipher::{self, Mode};
+use matasano_rust::{base64, utf8};
+
+use crate::data::read_data_file;
+
+/// Data file for 1.7, containing wrapped Base64 ciphertext
+const DATA_FILE: &str = "7.txt";
+/// AES-128 key for 1.7
+const KEY: &str = "YELLOW SUBMARINE";
+/// Expected start of the plaintext for 1.7
+const EXPECTED_PLAINTEXT_START: &str = "I'm back and I'm ringin' the bell";
+
+/// Check the data file for 1.7
+pub fn check() {
+    println!("Key: '{}'", KEY);
+    let aes = Aes::new(&utf8::utf8_encode(KEY)).expect("key is 16 bytes");
+
+    let text = read_data_file(DATA_FILE);
+    let ciphertext = base64::base64_decode_wrapped(&text);
+
+    let plaintext =
+        cipher::decrypt(&aes, Mode::Ecb, &[], &ciphertext).expect("ciphertext is whole blocks");
+    let plaintext = String::from_utf8_lossy(&plaintext);
+    println!("Plaintext:\n{}", plaintext);
+    println!("Expected plaintext start: '{}'", EXPECTED_PLAINTEXT_START);
+    assert!(plaintext.starts_with(EXPECTED_PLAINTEXT_START));
+}
@@ -0,0 +1,35 @@
+//! Set 1, Challenge 8, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::ecb::scan_ecb_hex_lines;
+
+use crate::data::read_data_file;
+
+/// Data file for 1.8, containing one hex ciphertext per line
+const DATA_FILE: &str = "8.txt";
+/// The number of candidate lines to print
+const CANDIDATE_COUNT: usize = 3;
+/// Expected start of the ECB encrypted line for 1.8
+const EXPECTED_LINE_START: &str = "d880619740a8a19b7840a8a31c810a3d";
+
+/// Check the data file for 1.8
+pub fn check() {
+    let text = read_data_file(DATA_FILE);
+
+    let ranked = scan_ecb_hex_lines(&text, AES_BLOCK_BYTES, CANDIDATE_COUNT);
+    for (line, repetition) in &ranked {
+        println!(
+            "Line: {}, duplicate blocks: {}, repeated block indexes: {:?}",
+            line, repetition.duplicate_count, repetition.repeated_indexes
+        );
+    }
+
+    let (line, repetition) = &ranked[0];
+    let hex = text.lines().nth(*line).expect("line exists").trim();
+    println!("ECB line: '{}'", hex);
+    println!("Expected line start: '{}'", EXPECTED_LINE_START);
+    assert!(repetition.has_repeats());
+    assert!(hex.starts_with(EXPECTED_LINE_START));
+}
@@ -0,0 +1,37 @@
+//! Set 2, Challenge 10, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::{Aes, AES_BLOCK_BYTES};
+use matasano_rust::cbc::cbc_decrypt_with_hook;
+use matasano_rust::{base64, padding, utf8};
+
+use crate::data::read_data_file;
+
+/// Data file for 2.10, containing wrapped Base64 ciphertext
+const DATA_FILE: &str = "10.txt";
+/// AES-128 key for 2.10
+const KEY: &str = "YELLOW SUBMARINE";
+/// Initialisation vector for 2.10
+const IV: [u8; AES_BLOCK_BYTES] = [0; AES_BLOCK_BYTES];
+/// Expected start of the plaintext for 2.10
+const EXPECTED_PLAINTEXT_START: &str = "I'm back and I'm ringin' the bell";
+
+/// Check the data file for 2.10
+pub fn check() {
+    println!("Key: '{}'", KEY);
+    let aes = Aes::new(&utf8::utf8_encode(KEY)).expect("key is 16 bytes");
+
+    let text = read_data_file(DATA_FILE);
+    let ciphertext = base64::base64_decode_wrapped(&text);
+
+    let mut block_count = 0;
+    let padded = cbc_decrypt_with_hook(&aes, &IV, &ciphertext, |_| block_count += 1)
+        .expect("ciphertext is whole blocks");
+    let plaintext =
+        padding::pkcs7_unpad(&padded, AES_BLOCK_BYTES).expect("plaintext has valid padding");
+    let plaintext = String::from_utf8_lossy(plaintext);
+    println!("Decrypted {} blocks:\n{}", block_count, plaintext);
+    println!("Expected plaintext start: '{}'", EXPECTED_PLAINTEXT_START);
+    assert!(plaintext.starts_with(EXPECTED_PLAINTEXT_START));
+}
@@ -0,0 +1,36 @@
+//! Set 2, Challenge 11, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::ecb::detect_oracle_ecb_or_cbc;
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::{ecb_cbc::EcbCbcOracle, Counting};
+use matasano_rust::rng::ChaCha20Rng;
+
+/// The number of detection trials for 2.11
+const TRIALS: usize = 2000;
+
+/// Check ECB/CBC detection against a random encryption oracle for 2.11
+pub fn check() {
+    let rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let mut oracle = Counting::new(EcbCbcOracle::new(rng));
+
+    let mut correct = 0;
+    for _ in 0..TRIALS {
+        let detected = detect_oracle_ecb_or_cbc(&mut oracle, AES_BLOCK_BYTES);
+        let actual = oracle.inner().last_mode().expect("oracle has been queried");
+        if detected == actual {
+            correct += 1;
+        }
+    }
+
+    println!(
+        "Detected {} of {} modes correctly, using {} oracle queries",
+        correct,
+        TRIALS,
+        oracle.queries()
+    );
+    assert_eq!(correct, TRIALS);
+}
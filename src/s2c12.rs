@@ -0,0 +1,39 @@
+//! Set 2, Challenge 12, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::ecb::byte_at_a_time_ecb;
+use matasano_rust::base64;
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::{ecb_suffix::EcbSuffixOracle, Counting};
+use matasano_rust::rng::ChaCha20Rng;
+
+/// Base64 secret appended by the oracle for 2.12 and 2.14
+pub const SECRET: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
+/// Expected start of the recovered secret for 2.12 and 2.14
+pub const EXPECTED_SECRET_START: &str = "Rollin' in my 5.0\n";
+
+/// Check byte-at-a-time ECB decryption for 2.12
+pub fn check() {
+    let mut rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let secret = base64::base64_decode(SECRET);
+    let mut oracle = Counting::new(EcbSuffixOracle::new(&mut rng, &secret));
+
+    let recovery = byte_at_a_time_ecb(&mut oracle).expect("oracle uses ECB mode");
+    println!(
+        "Block size: {}, recovered {} secret bytes using {} oracle queries:\n{}",
+        recovery.block_size,
+        recovery.secret.len(),
+        oracle.queries(),
+        String::from_utf8_lossy(&recovery.secret)
+    );
+    println!("Expected secret start: {:?}", EXPECTED_SECRET_START);
+    assert_eq!(recovery.block_size, AES_BLOCK_BYTES);
+    assert_eq!(recovery.prefix_len, 0);
+    assert!(recovery
+        .secret
+        .starts_with(EXPECTED_SECRET_START.as_bytes()));
+    assert_eq!(recovery.secret, secret);
+}
@@ -0,0 +1,47 @@
+//! Set 2, Challenge 13, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::ecb::ecb_cut_and_paste;
+use matasano_rust::cookie::{profile_for, Cookie};
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::{profile::ProfileOracle, Counting};
+use matasano_rust::rng::ChaCha20Rng;
+
+/// Example cookie for 2.13
+const COOKIE: &str = "foo=bar&baz=qux&zap=zazzle";
+/// Example email address for 2.13, which tries to add a role
+const EMAIL: &str = "foo@bar.com&role=admin";
+/// Expected encoding of the example email profile
+const EXPECTED_PROFILE: &str = "email=foo@bar.comroleadmin&uid=10&role=user";
+/// The role forged by the cut-and-paste attack for 2.13
+const TARGET_ROLE: &str = "admin";
+
+/// Check the cookie parser and ECB cut-and-paste attack for 2.13
+pub fn check() {
+    let cookie = Cookie::parse(COOKIE).expect("cookie is valid");
+    println!("Parsed cookie: {:?}", cookie);
+    assert_eq!(cookie.get("baz"), Some("qux"));
+    assert_eq!(cookie.encode(), COOKIE);
+
+    let profile = profile_for(EMAIL).encode();
+    println!("Profile: '{}'", profile);
+    println!("Expected profile: '{}'", EXPECTED_PROFILE);
+    assert_eq!(profile, EXPECTED_PROFILE);
+
+    let mut rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let mut oracle = Counting::new(ProfileOracle::new(&mut rng));
+    let forged = ecb_cut_and_paste(&mut oracle, AES_BLOCK_BYTES, TARGET_ROLE);
+    let profile = oracle
+        .inner()
+        .decrypt_profile(&forged)
+        .expect("forged profile is valid");
+    println!(
+        "Forged profile using {} oracle queries: {:?}",
+        oracle.queries(),
+        profile
+    );
+    assert_eq!(profile.get("role"), Some(TARGET_ROLE));
+}
@@ -0,0 +1,37 @@
+//! Set 2, Challenge 14, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::ecb::byte_at_a_time_ecb;
+use matasano_rust::base64;
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::{ecb_suffix::EcbSuffixOracle, Counting};
+use matasano_rust::rng::ChaCha20Rng;
+
+use crate::s2c12::{EXPECTED_SECRET_START, SECRET};
+
+/// Check byte-at-a-time ECB decryption with a random prefix for 2.14
+pub fn check() {
+    let mut rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let secret = base64::base64_decode(SECRET);
+    let mut oracle = Counting::new(EcbSuffixOracle::with_random_prefix(&mut rng, &secret));
+
+    let recovery = byte_at_a_time_ecb(&mut oracle).expect("oracle uses ECB mode");
+    println!(
+        "Block size: {}, prefix length: {}, recovered {} secret bytes using {} oracle queries:\n{}",
+        recovery.block_size,
+        recovery.prefix_len,
+        recovery.secret.len(),
+        oracle.queries(),
+        String::from_utf8_lossy(&recovery.secret)
+    );
+    println!("Expected secret start: {:?}", EXPECTED_SECRET_START);
+    assert_eq!(recovery.block_size, AES_BLOCK_BYTES);
+    assert_eq!(recovery.prefix_len, oracle.inner().prefix_len());
+    assert!(recovery
+        .secret
+        .starts_with(EXPECTED_SECRET_START.as_bytes()));
+    assert_eq!(recovery.secret, secret);
+}
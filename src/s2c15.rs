@@ -0,0 +1,35 @@
+//! Set 2, Challenge 15, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::{padding, utf8};
+
+/// Validly padded test vector for 2.15
+const VALID_TEST: &str = "ICE ICE BABY\x04\x04\x04\x04";
+/// Expected unpadded output for the valid test vector
+const VALID_EXPECTED_TEST_OUTPUT: &str = "ICE ICE BABY";
+/// Invalidly padded test vectors for 2.15
+const INVALID_TESTS: [&str; 2] = [
+    "ICE ICE BABY\x05\x05\x05\x05",
+    "ICE ICE BABY\x01\x02\x03\x04",
+];
+
+/// Check the test vectors for 2.15
+pub fn check() {
+    println!("Valid padding test: {:?}", VALID_TEST);
+    let bytes_test = utf8::utf8_encode(VALID_TEST);
+    let unpadded = padding::pkcs7_unpad(&bytes_test, AES_BLOCK_BYTES).expect("padding is valid");
+    let unpadded = utf8::utf8_decode_str(unpadded);
+    println!("Unpadded: '{}'", unpadded);
+    println!("Expected output: '{}'", VALID_EXPECTED_TEST_OUTPUT);
+    assert!(unpadded == VALID_EXPECTED_TEST_OUTPUT);
+
+    for test in INVALID_TESTS {
+        println!("Invalid padding test: {:?}", test);
+        let bytes_test = utf8::utf8_encode(test);
+        let result = padding::pkcs7_unpad(&bytes_test, AES_BLOCK_BYTES);
+        println!("Unpad result: {:?}", result);
+        assert!(result.is_err());
+    }
+}
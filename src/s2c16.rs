@@ -0,0 +1,38 @@
+//! Set 2, Challenge 16, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::cbc::cbc_bitflip;
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::comments::{CommentOracle, ADMIN_TOKEN, COMMENT_PREFIX};
+use matasano_rust::oracle::{Counting, Oracle};
+use matasano_rust::rng::ChaCha20Rng;
+
+/// Check the CBC bitflipping attack for 2.16
+pub fn check() {
+    let rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let mut oracle = Counting::new(CommentOracle::new(rng));
+
+    // Quoting stops the token being submitted directly
+    let direct = oracle.query(ADMIN_TOKEN);
+    assert_eq!(oracle.inner().is_admin(&direct), Ok(false));
+
+    let flipped = cbc_bitflip(
+        &mut oracle,
+        AES_BLOCK_BYTES,
+        COMMENT_PREFIX.len(),
+        ADMIN_TOKEN,
+    );
+    let comment = oracle
+        .inner()
+        .decrypt_comment(&flipped)
+        .expect("flipped comment is valid");
+    println!(
+        "Flipped comment using {} oracle queries: {}",
+        oracle.queries(),
+        String::from_utf8_lossy(&comment)
+    );
+    assert_eq!(oracle.inner().is_admin(&flipped), Ok(true));
+}
@@ -0,0 +1,22 @@
+//! Set 2, Challenge 9, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::{padding, utf8};
+
+/// Input plaintext test vector for 2.9
+const PLAINTEXT_TEST: &str = "YELLOW SUBMARINE";
+/// Block size for 2.9
+const BLOCK_SIZE: usize = 20;
+/// Expected output test vector for 2.9
+const EXPECTED_TEST_OUTPUT: &str = "YELLOW SUBMARINE\x04\x04\x04\x04";
+
+/// Check the test vectors for 2.9
+pub fn check() {
+    println!("Plaintext test: '{}'", PLAINTEXT_TEST);
+    let padded = padding::pkcs7_pad(&utf8::utf8_encode(PLAINTEXT_TEST), BLOCK_SIZE);
+    let padded = utf8::utf8_decode(&padded);
+    println!("PKCS#7 padded to {} bytes: {:?}", BLOCK_SIZE, padded);
+    println!("Expected output: {:?}", EXPECTED_TEST_OUTPUT);
+    assert!(padded == EXPECTED_TEST_OUTPUT);
+}
@@ -0,0 +1,61 @@
+//! Set 3, Challenge 17, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::AES_BLOCK_BYTES;
+use matasano_rust::attack::cbc::{padding_oracle_decrypt, padding_oracle_encrypt};
+use matasano_rust::base64;
+use matasano_rust::hex::hex_encode;
+use matasano_rust::oracle::{padding::CbcPaddingOracle, Counting};
+use matasano_rust::padding::pkcs7_unpad;
+use matasano_rust::rng::{ChaCha20Rng, Rng};
+
+/// Base64 plaintexts for 3.17, one of which is chosen at random
+const PLAINTEXTS: [&str; 10] = [
+    "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
+    "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
+    "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
+    "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
+    "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
+    "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==",
+    "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
+    "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
+    "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
+    "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
+];
+/// Plaintext encrypted using only the padding oracle for 3.17
+const FORGED_PLAINTEXT: &str = "Forged using only a padding oracle";
+
+/// Check the CBC padding oracle attack for 3.17
+pub fn check() {
+    let mut rng = ChaCha20Rng::from_os_entropy();
+    println!("Random seed: {}", hex_encode(&rng.seed()));
+    let choice = rng.next_below(PLAINTEXTS.len() as u64) as usize;
+    let mut oracle = Counting::new(CbcPaddingOracle::new(rng));
+
+    let expected = base64::base64_decode(PLAINTEXTS[choice]);
+    let data = oracle.inner_mut().encrypt(&expected);
+    let padded =
+        padding_oracle_decrypt(&mut oracle, AES_BLOCK_BYTES, &data).expect("oracle is consistent");
+    let plaintext = pkcs7_unpad(&padded, AES_BLOCK_BYTES).expect("plaintext has valid padding");
+    println!(
+        "Decrypted plaintext {} using {} oracle queries: {:?}",
+        choice,
+        oracle.queries(),
+        String::from_utf8_lossy(plaintext)
+    );
+    assert_eq!(plaintext, expected);
+
+    let forged = padding_oracle_encrypt(&mut oracle, AES_BLOCK_BYTES, FORGED_PLAINTEXT.as_bytes())
+        .expect("oracle is consistent");
+    let decrypted = oracle
+        .inner()
+        .decrypt(&forged)
+        .expect("forged data is valid");
+    println!(
+        "Encrypted {:?} after {} oracle queries in total",
+        FORGED_PLAINTEXT,
+        oracle.queries()
+    );
+    assert_eq!(decrypted, FORGED_PLAINTEXT.as_bytes());
+}
@@ -0,0 +1,41 @@
+//! Set 3, Challenge 18, Matasano Cryptopals Challenges
+
+#![deny(missing_docs)]
+
+use matasano_rust::aes::Aes;
+use matasano_rust::ctr::{Ctr, CtrLayout};
+use matasano_rust::{base64, utf8};
+
+/// Base64 ciphertext for 3.18
+const CIPHERTEXT: &str = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
+/// AES-128 key for 3.18
+const KEY: &str = "YELLOW SUBMARINE";
+/// Nonce for 3.18
+const NONCE: [u8; 8] = [0; 8];
+/// Expected start of the plaintext for 3.18
+const EXPECTED_PLAINTEXT_START: &str = "Yo, VIP Let's kick it Ice, Ice, baby";
+
+/// Check CTR decryption for 3.18
+pub fn check() {
+    println!("Key: '{}'", KEY);
+    let aes = Aes::new(&utf8::utf8_encode(KEY)).expect("key is 16 bytes");
+    let mut ctr =
+        Ctr::new(aes, CtrLayout::NONCE_64_COUNTER_64_LE, &NONCE).expect("layout fits AES");
+
+    let mut data = base64::base64_decode(CIPHERTEXT);
+    ctr.apply_keystream(&mut data);
+    let plaintext = String::from_utf8_lossy(&data).into_owned();
+    println!("Decrypted: '{}'", plaintext);
+    println!("Expected plaintext start: '{}'", EXPECTED_PLAINTEXT_START);
+    assert!(plaintext.starts_with(EXPECTED_PLAINTEXT_START));
+
+    // Decrypting from a later offset gives the same plaintext
+    let mut tail = base64::base64_decode(CIPHERTEXT).split_off(20);
+    ctr.apply_keystream_at(20, &mut tail);
+    assert_eq!(tail, data[20..]);
+
+    // Encrypting the plaintext again, in place, gives the ciphertext
+    ctr.seek(0);
+    ctr.apply_keystream(&mut data);
+    assert_eq!(base64::base64_encode(&data), CIPHERTEXT);
+}
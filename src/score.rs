@@ -0,0 +1,468 @@
+//! English plaintext scoring and frequency analysis
+//!
+//! Scores are normalised by the text length, so texts of different lengths can be compared.
+//! Higher scores are more likely to be English.
+
+#![deny(missing_docs)]
+
+use core::cmp::Ordering;
+
+use alloc::vec::Vec;
+
+use crate::utf8::utf8_try_decode_str;
+
+/// The number of letters in the English alphabet
+pub const LETTER_COUNT: usize = 26;
+
+/// The frequency of each lowercase letter, then space, in English text.
+/// Upper case letters are counted as lower case.
+pub const ENGLISH_FREQUENCIES: [f64; LETTER_COUNT + 1] = [
+    0.0651738, 0.0124248, 0.0217339, 0.0349835, 0.1041442, 0.0197881, 0.0158610, 0.0492888,
+    0.0558094, 0.0009033, 0.0050529, 0.0331490, 0.0202124, 0.0564513, 0.0596302, 0.0137645,
+    0.0008606, 0.0497563, 0.0515760, 0.0729357, 0.0225134, 0.0082903, 0.0171272, 0.0013692,
+    0.0145984, 0.0007836, 0.1918182,
+];
+
+/// The index of space in ENGLISH_FREQUENCIES
+const SPACE_INDEX: usize = LETTER_COUNT;
+
+/// The proportion of English text which is printable, but not a letter or space.
+/// For example: digits, punctuation, and newlines.
+pub const OTHER_FREQUENCY: f64 = 0.02;
+
+/// The number of printable ASCII characters which are not letters or space, plus tab, newline,
+/// and carriage return
+const OTHER_PRINTABLE_COUNT: usize = 95 - 2 * LETTER_COUNT - 1 + 3;
+
+/// The probability used for characters and n-grams that never appear in English text.
+/// Must be non-zero, so its logarithm is finite.
+const FLOOR_PROBABILITY: f64 = 1e-6;
+
+/// The penalty subtracted from the total score for each non-printable character
+pub const NON_PRINTABLE_PENALTY: f64 = 20.0;
+
+/// The most common English bigrams, and their frequencies among letter bigrams
+pub const ENGLISH_BIGRAMS: [(&[u8; 2], f64); 40] = [
+    (b"th", 0.0356),
+    (b"he", 0.0307),
+    (b"in", 0.0243),
+    (b"er", 0.0205),
+    (b"an", 0.0199),
+    (b"re", 0.0185),
+    (b"on", 0.0176),
+    (b"at", 0.0149),
+    (b"en", 0.0145),
+    (b"nd", 0.0135),
+    (b"ti", 0.0134),
+    (b"es", 0.0134),
+    (b"or", 0.0128),
+    (b"te", 0.0120),
+    (b"of", 0.0117),
+    (b"ed", 0.0117),
+    (b"is", 0.0113),
+    (b"it", 0.0112),
+    (b"al", 0.0109),
+    (b"ar", 0.0107),
+    (b"st", 0.0105),
+    (b"to", 0.0104),
+    (b"nt", 0.0104),
+    (b"ng", 0.0095),
+    (b"se", 0.0093),
+    (b"ha", 0.0093),
+    (b"as", 0.0087),
+    (b"ou", 0.0087),
+    (b"io", 0.0083),
+    (b"le", 0.0083),
+    (b"ve", 0.0083),
+    (b"co", 0.0079),
+    (b"me", 0.0079),
+    (b"de", 0.0076),
+    (b"hi", 0.0076),
+    (b"ri", 0.0073),
+    (b"ro", 0.0073),
+    (b"ic", 0.0070),
+    (b"ne", 0.0069),
+    (b"ea", 0.0069),
+];
+
+/// The most common English trigrams, and their frequencies among letter trigrams
+pub const ENGLISH_TRIGRAMS: [(&[u8; 3], f64); 20] = [
+    (b"the", 0.0351),
+    (b"and", 0.0159),
+    (b"ing", 0.0115),
+    (b"her", 0.0082),
+    (b"hat", 0.0065),
+    (b"his", 0.0060),
+    (b"tha", 0.0059),
+    (b"ere", 0.0056),
+    (b"for", 0.0055),
+    (b"ent", 0.0053),
+    (b"ion", 0.0051),
+    (b"ter", 0.0046),
+    (b"was", 0.0046),
+    (b"you", 0.0044),
+    (b"ith", 0.0043),
+    (b"ver", 0.0043),
+    (b"all", 0.0042),
+    (b"wit", 0.0040),
+    (b"thi", 0.0039),
+    (b"tio", 0.0038),
+];
+
+/// The probability of an n-gram that is not in the table.
+/// Rare n-grams are much more likely than n-grams that never appear in English.
+const RARE_NGRAM_PROBABILITY: f64 = 1e-4;
+
+/// Returns the index of byte in ENGLISH_FREQUENCIES, or None if it is not a letter or space.
+fn frequency_index(byte: u8) -> Option<usize> {
+    match byte {
+        b'a'..=b'z' => Some((byte - b'a') as usize),
+        b'A'..=b'Z' => Some((byte - b'A') as usize),
+        b' ' => Some(SPACE_INDEX),
+        _ => None,
+    }
+}
+
+/// Returns true if byte is printable ASCII, or common ASCII whitespace.
+fn is_printable_ascii(byte: u8) -> bool {
+    matches!(byte, b' '..=b'~' | b'\t' | b'\n' | b'\r')
+}
+
+/// Returns the probability of byte in English text.
+fn byte_probability(byte: u8) -> f64 {
+    match frequency_index(byte) {
+        Some(i) => ENGLISH_FREQUENCIES[i] * (1.0 - OTHER_FREQUENCY),
+        None if is_printable_ascii(byte) => OTHER_FREQUENCY / OTHER_PRINTABLE_COUNT as f64,
+        None => FLOOR_PROBABILITY,
+    }
+}
+
+/// Returns the count of each letter, then space, in text, and the count of all other bytes.
+/// Upper case letters are counted as lower case.
+pub fn letter_counts(text: &[u8]) -> ([usize; LETTER_COUNT + 1], usize) {
+    let mut counts = [0; LETTER_COUNT + 1];
+    let mut other = 0;
+
+    for &byte in text {
+        match frequency_index(byte) {
+            Some(i) => counts[i] += 1,
+            None => other += 1,
+        }
+    }
+
+    (counts, other)
+}
+
+/// Returns the index of coincidence of the bytes in text: the probability that two bytes chosen
+/// from different positions in text are equal.
+///
+/// English text has a higher index of coincidence than random bytes. Returns zero if text is
+/// shorter than two bytes.
+pub fn index_of_coincidence(text: &[u8]) -> f64 {
+    if text.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in text {
+        counts[byte as usize] += 1;
+    }
+
+    let matches: usize = counts.iter().map(|&n| n * n.saturating_sub(1)).sum();
+    let len = text.len();
+
+    matches as f64 / (len * (len - 1)) as f64
+}
+
+/// Returns the number of non-printable characters in text.
+///
+/// If text is valid UTF-8, counts control characters, other than tab, newline, and carriage
+/// return. Otherwise, counts every byte that is not printable ASCII.
+pub fn non_printable_count(text: &[u8]) -> usize {
+    match utf8_try_decode_str(text) {
+        Some(s) => s
+            .chars()
+            .filter(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            .count(),
+        None => text.iter().filter(|&&b| !is_printable_ascii(b)).count(),
+    }
+}
+
+/// Returns the chi-squared statistic of text, compared with English letter and space
+/// frequencies. All other bytes are compared with OTHER_FREQUENCY.
+///
+/// Lower values are more likely to be English. Returns zero for empty text.
+pub fn chi_squared(text: &[u8]) -> f64 {
+    let (counts, other) = letter_counts(text);
+    let len = text.len() as f64;
+
+    let mut chi_squared = 0.0;
+    for (&count, &frequency) in counts.iter().zip(ENGLISH_FREQUENCIES.iter()) {
+        let expected = frequency * (1.0 - OTHER_FREQUENCY) * len;
+        if expected > 0.0 {
+            let diff = count as f64 - expected;
+            chi_squared += diff * diff / expected;
+        }
+    }
+
+    let expected = OTHER_FREQUENCY * len;
+    if expected > 0.0 {
+        let diff = other as f64 - expected;
+        chi_squared += diff * diff / expected;
+    }
+
+    chi_squared
+}
+
+/// Returns the mean log-likelihood of each byte in text, using English character frequencies.
+///
+/// Higher values are more likely to be English. Returns zero for empty text.
+pub fn log_likelihood(text: &[u8]) -> f64 {
+    if text.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = text.iter().map(|&b| byte_probability(b).ln()).sum();
+    total / text.len() as f64
+}
+
+/// Returns the mean log-likelihood of each n-gram of letters in text, using table.
+///
+/// Only n-grams made entirely of letters are scored. If text has no letter n-grams, it is scored
+/// as a single rare n-gram.
+fn ngram_log_likelihood<const N: usize>(text: &[u8], table: &[(&[u8; N], f64)]) -> f64 {
+    let mut total = 0.0;
+    let mut count = 0;
+
+    for window in text.windows(N) {
+        if !window.iter().all(u8::is_ascii_alphabetic) {
+            continue;
+        }
+
+        let probability = table
+            .iter()
+            .find(|(ngram, _)| window.eq_ignore_ascii_case(&ngram[..]))
+            .map_or(RARE_NGRAM_PROBABILITY, |&(_, frequency)| frequency);
+        total += probability.ln();
+        count += 1;
+    }
+
+    if count == 0 {
+        return RARE_NGRAM_PROBABILITY.ln();
+    }
+
+    total / count as f64
+}
+
+/// Returns the mean log-likelihood of each letter bigram in text.
+///
+/// Higher values are more likely to be English.
+pub fn bigram_log_likelihood(text: &[u8]) -> f64 {
+    ngram_log_likelihood(text, &ENGLISH_BIGRAMS)
+}
+
+/// Returns the mean log-likelihood of each letter trigram in text.
+///
+/// Higher values are more likely to be English.
+pub fn trigram_log_likelihood(text: &[u8]) -> f64 {
+    ngram_log_likelihood(text, &ENGLISH_TRIGRAMS)
+}
+
+/// A method for scoring English plaintext.
+///
+/// Every method subtracts NON_PRINTABLE_PENALTY for each non-printable character, normalised by
+/// the text length.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Scorer {
+    /// The negated chi-squared statistic of the character frequencies
+    ChiSquared,
+    /// The log-likelihood of the character frequencies
+    #[default]
+    LogLikelihood,
+    /// The character log-likelihood, plus the bigram and trigram log-likelihoods.
+    /// Slower, but more accurate for short or mixed-case texts.
+    Ngram,
+}
+
+impl Scorer {
+    /// Returns the score of text. Higher scores are more likely to be English.
+    pub fn score(self, text: &[u8]) -> f64 {
+        if text.is_empty() {
+            return 0.0;
+        }
+
+        let score = match self {
+            Scorer::ChiSquared => -chi_squared(text) / text.len() as f64,
+            Scorer::LogLikelihood => log_likelihood(text),
+            Scorer::Ngram => {
+                log_likelihood(text) + bigram_log_likelihood(text) + trigram_log_likelihood(text)
+            }
+        };
+
+        score - NON_PRINTABLE_PENALTY * non_printable_count(text) as f64 / text.len() as f64
+    }
+}
+
+/// A candidate decryption, with the key that produced it, and its score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate<K> {
+    /// The key used to decrypt the plaintext
+    pub key: K,
+    /// The candidate plaintext
+    pub plaintext: Vec<u8>,
+    /// The plaintext's score. Higher scores are more likely to be English.
+    pub score: f64,
+}
+
+impl<K> Candidate<K> {
+    /// Returns a new candidate, scoring plaintext using scorer.
+    pub fn new(key: K, plaintext: Vec<u8>, scorer: Scorer) -> Self {
+        let score = scorer.score(&plaintext);
+        Candidate {
+            key,
+            plaintext,
+            score,
+        }
+    }
+
+    /// Compares candidates, so that better candidates sort first.
+    fn cmp_best_first(&self, other: &Self) -> Ordering {
+        other.score.total_cmp(&self.score)
+    }
+}
+
+/// Returns the top_n best candidates, scored using scorer, best first.
+///
+/// Candidates with equal scores keep their original order.
+pub fn rank_candidates<K, I>(candidates: I, scorer: Scorer, top_n: usize) -> Vec<Candidate<K>>
+where
+    I: IntoIterator<Item = (K, Vec<u8>)>,
+{
+    let mut ranked: Vec<Candidate<K>> = candidates
+        .into_iter()
+        .map(|(key, plaintext)| Candidate::new(key, plaintext, scorer))
+        .collect();
+
+    ranked.sort_by(Candidate::cmp_best_first);
+    ranked.truncate(top_n);
+
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &[u8] = b"Now that the party is jumping, we are cooking like a pound of bacon";
+
+    const SCORERS: [Scorer; 3] = [Scorer::ChiSquared, Scorer::LogLikelihood, Scorer::Ngram];
+
+    #[test]
+    fn frequencies() {
+        let total: f64 = ENGLISH_FREQUENCIES.iter().sum();
+        assert!((total - 1.0).abs() < 1e-3, "frequencies sum to {}", total);
+        assert_eq!(OTHER_PRINTABLE_COUNT, 45);
+    }
+
+    #[test]
+    fn counts() {
+        let (counts, other) = letter_counts(b"aA z!\n");
+        assert_eq!(counts[0], 2);
+        assert_eq!(counts[25], 1);
+        assert_eq!(counts[SPACE_INDEX], 1);
+        assert_eq!(other, 2);
+    }
+
+    #[test]
+    fn coincidence() {
+        assert_eq!(index_of_coincidence(b""), 0.0);
+        assert_eq!(index_of_coincidence(b"a"), 0.0);
+        assert_eq!(index_of_coincidence(b"aa"), 1.0);
+        assert_eq!(index_of_coincidence(b"ab"), 0.0);
+        assert_eq!(index_of_coincidence(b"aab"), 1.0 / 3.0);
+
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(index_of_coincidence(&all_bytes), 0.0);
+        assert!(index_of_coincidence(ENGLISH) > 0.05);
+    }
+
+    #[test]
+    fn non_printable() {
+        assert_eq!(non_printable_count(b""), 0);
+        assert_eq!(non_printable_count(b"Hello,\tworld!\r\n"), 0);
+        assert_eq!(non_printable_count(b"\x00a\x1b\x7f"), 3);
+        // Valid UTF-8 letters are printable
+        assert_eq!(non_printable_count("caf\u{e9}".as_bytes()), 0);
+        // Invalid UTF-8 counts every non-ASCII byte
+        assert_eq!(non_printable_count(b"ab\xff\xc3"), 2);
+    }
+
+    #[test]
+    fn english_beats_xored() {
+        for scorer in SCORERS {
+            let english = scorer.score(ENGLISH);
+            for key in 1..=255 {
+                let xored: Vec<u8> = ENGLISH.iter().map(|b| b ^ key).collect();
+                assert!(
+                    english > scorer.score(&xored),
+                    "{:?} prefers key {:#04x}",
+                    scorer,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        assert_eq!(chi_squared(b""), 0.0);
+        assert_eq!(log_likelihood(b""), 0.0);
+        assert_eq!(bigram_log_likelihood(b"a b"), RARE_NGRAM_PROBABILITY.ln());
+        assert_eq!(trigram_log_likelihood(b"ab"), RARE_NGRAM_PROBABILITY.ln());
+
+        assert!(chi_squared(ENGLISH) < chi_squared(b"zzzzqqqqxxxxjjjj"));
+        assert!(log_likelihood(ENGLISH) > log_likelihood(b"zzzzqqqqxxxxjjjj"));
+        assert!(bigram_log_likelihood(b"THE") > bigram_log_likelihood(b"zqx"));
+        assert!(trigram_log_likelihood(b"The") > trigram_log_likelihood(b"zqx"));
+    }
+
+    #[test]
+    fn penalty() {
+        for scorer in SCORERS {
+            assert_eq!(scorer.score(b""), 0.0);
+            assert!(scorer.score(b"etaoin shrdlu") > scorer.score(b"etaoin\x00shrdlu"));
+        }
+    }
+
+    #[test]
+    fn ranking() {
+        let candidates = (0..=255u8).map(|key| {
+            let plaintext: Vec<u8> = ENGLISH.iter().map(|b| b ^ key).collect();
+            (key, plaintext)
+        });
+        let ranked = rank_candidates(candidates, Scorer::default(), 3);
+
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(ranked[0].key, 0);
+        assert_eq!(ranked[0].plaintext, ENGLISH);
+        assert!(ranked[0].score >= ranked[1].score);
+        assert!(ranked[1].score >= ranked[2].score);
+
+        assert!(rank_candidates(Vec::<(u8, Vec<u8>)>::new(), Scorer::Ngram, 3).is_empty());
+    }
+
+    #[test]
+    fn ranking_is_stable() {
+        let ranked = rank_candidates(
+            [
+                (1, b"abc".to_vec()),
+                (2, b"abc".to_vec()),
+                (3, b"\x00".to_vec()),
+            ],
+            Scorer::LogLikelihood,
+            10,
+        );
+        let keys: Vec<i32> = ranked.iter().map(|c| c.key).collect();
+        assert_eq!(keys, [1, 2, 3]);
+    }
+}
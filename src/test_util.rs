@@ -0,0 +1,69 @@
+//! Shared test constants and helpers
+
+#![deny(missing_docs)]
+
+use alloc::vec::Vec;
+
+use crate::cipher::Mode;
+use crate::hex::hex_decode;
+use crate::math::BigUint;
+
+/// The 1536-bit MODP Diffie-Hellman prime from RFC 3526, as used in the NIST DH examples.
+/// It is a safe prime.
+pub const NIST_P_HEX: &str = "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff";
+
+/// Returns a deterministic pseudo-random byte generator, using a 64-bit LCG
+pub fn byte_generator(seed: u64) -> impl FnMut() -> u8 {
+    let mut state = seed;
+    move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 56) as u8
+    }
+}
+
+/// Returns a deterministic pseudo-random generator for BigUints of len bytes
+pub fn biguint_generator(seed: u64) -> impl FnMut(usize) -> BigUint {
+    let mut next = byte_generator(seed);
+    move |len| {
+        let bytes: Vec<u8> = (0..len).map(|_| next()).collect();
+        BigUint::from_bytes_be(&bytes)
+    }
+}
+
+/// The AES-128 key for the NIST SP 800-38A examples
+pub const SP_800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+/// The IV for the NIST SP 800-38A CBC, CFB, and OFB examples
+pub const SP_800_38A_IV: &str = "000102030405060708090a0b0c0d0e0f";
+/// The initial counter block for the NIST SP 800-38A CTR example
+pub const SP_800_38A_COUNTER: &str = "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+/// The plaintext for the NIST SP 800-38A examples
+pub const SP_800_38A_PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+/// The NIST SP 800-38A AES-128 ciphertexts for each mode
+pub const SP_800_38A_CIPHERTEXTS: [(Mode, &str); 5] = [
+    (Mode::Ecb, "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4"),
+    (Mode::Cbc, "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b273bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7"),
+    (Mode::Cfb, "3b3fd92eb72dad20333449f8e83cfb4ac8a64537a0b3a93fcde3cdad9f1ce58b26751f67a3cbb140b1808cf187a4f4dfc04b05357c5d1c0eeac4c66f9ff7f2e6"),
+    (Mode::Ofb, "3b3fd92eb72dad20333449f8e83cfb4a7789508d16918f03f53c52dac54ed8259740051e9c5fecf64344f7a82260edcc304c6528f659c77866a510d9c1d6ae5e"),
+    (Mode::Ctr, "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"),
+];
+
+/// Returns the SP 800-38A IV for mode.
+pub fn sp_800_38a_iv(mode: Mode) -> Vec<u8> {
+    match mode {
+        Mode::Ecb => Vec::new(),
+        Mode::Ctr => hex_decode(SP_800_38A_COUNTER),
+        Mode::Cbc | Mode::Cfb | Mode::Ofb => hex_decode(SP_800_38A_IV),
+    }
+}
+
+/// Returns the SP 800-38A AES-128 ciphertext for mode.
+pub fn sp_800_38a_ciphertext(mode: Mode) -> Vec<u8> {
+    let (_, ciphertext) = SP_800_38A_CIPHERTEXTS
+        .iter()
+        .find(|(m, _)| *m == mode)
+        .expect("every mode has an example");
+    hex_decode(ciphertext)
+}
@@ -2,19 +2,64 @@
 
 #![deny(missing_docs)]
 
+use core::str;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+
 /// Encode s to bytes.
+#[cfg(feature = "alloc")]
 pub fn utf8_encode(s: &str) -> Vec<u8> {
     s.bytes().collect()
 }
 
 /// Decode s from bytes.
 /// Panics on invalid UTF-8.
+#[cfg(feature = "alloc")]
 pub fn utf8_decode(utf8_bytes: &[u8]) -> String {
-    let r = String::from_utf8(utf8_bytes.to_vec());
+    utf8_decode_str(utf8_bytes).to_owned()
+}
+
+/// Decode a str borrowed from bytes. Does not allocate.
+/// Panics on invalid UTF-8.
+pub fn utf8_decode_str(utf8_bytes: &[u8]) -> &str {
+    let r = utf8_try_decode_str(utf8_bytes);
     r.expect("utf8_bytes must be valid UTF-8")
 }
 
+/// Decode a str borrowed from bytes. Does not allocate.
+/// Returns None on invalid UTF-8.
+pub fn utf8_try_decode_str(utf8_bytes: &[u8]) -> Option<&str> {
+    str::from_utf8(utf8_bytes).ok()
+}
+
 #[cfg(test)]
+mod slice_tests {
+    use super::*;
+
+    #[test]
+    fn decode_str() {
+        assert_eq!(utf8_decode_str(&[]), "");
+        assert_eq!(utf8_decode_str(&[84, 101, 115, 116]), "Test");
+        assert_eq!(utf8_decode_str(&[0b11000011, 0b10101001]), "\u{00E9}");
+    }
+
+    #[test]
+    fn try_decode_str() {
+        assert_eq!(utf8_try_decode_str(&[84, 101, 115, 116]), Some("Test"));
+        assert_eq!(utf8_try_decode_str(&[0xC0]), None);
+        assert_eq!(utf8_try_decode_str(&[0xFF]), None);
+        assert_eq!(utf8_try_decode_str(&[0xC3]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be valid UTF-8")]
+    fn invalid_utf8_decode_str() {
+        utf8_decode_str(&[0xC3]);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
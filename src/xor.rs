@@ -0,0 +1,179 @@
+//! XOR of byte buffers
+
+#![deny(missing_docs)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// The reasons an XOR operation can fail
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum XorError {
+    /// The buffers have different lengths
+    LengthMismatch {
+        /// The length of the left buffer
+        left: usize,
+        /// The length of the right buffer
+        right: usize,
+    },
+}
+
+impl fmt::Display for XorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XorError::LengthMismatch { left, right } => write!(
+                f,
+                "XOR buffers must be the same length, but they are {} and {} bytes long",
+                left, right
+            ),
+        }
+    }
+}
+
+/// XOR src into dest, in place. Does not allocate.
+///
+/// Returns an error if dest and src are different lengths.
+pub fn fixed_xor_in_place(dest: &mut [u8], src: &[u8]) -> Result<(), XorError> {
+    if dest.len() != src.len() {
+        return Err(XorError::LengthMismatch {
+            left: dest.len(),
+            right: src.len(),
+        });
+    }
+
+    for (d, s) in dest.iter_mut().zip(src) {
+        *d ^= s;
+    }
+
+    Ok(())
+}
+
+/// Returns the XOR of two equal-length buffers.
+///
+/// Returns an error if left and right are different lengths.
+#[cfg(feature = "alloc")]
+pub fn fixed_xor(left: &[u8], right: &[u8]) -> Result<Vec<u8>, XorError> {
+    let mut v = left.to_vec();
+    fixed_xor_in_place(&mut v, right)?;
+
+    Ok(v)
+}
+
+/// XOR every byte in dest with key, in place. Does not allocate.
+pub fn single_byte_xor_in_place(dest: &mut [u8], key: u8) {
+    for d in dest.iter_mut() {
+        *d ^= key;
+    }
+}
+
+/// Returns bytes, with every byte XORed with key.
+#[cfg(feature = "alloc")]
+pub fn single_byte_xor(bytes: &[u8], key: u8) -> Vec<u8> {
+    let mut v = bytes.to_vec();
+    single_byte_xor_in_place(&mut v, key);
+
+    v
+}
+
+/// XOR dest with key, repeating key as many times as needed, in place. Does not allocate.
+///
+/// Panics if key is empty.
+pub fn repeating_key_xor_in_place(dest: &mut [u8], key: &[u8]) {
+    assert!(!key.is_empty(), "Repeating XOR key must not be empty");
+
+    for (d, k) in dest.iter_mut().zip(key.iter().cycle()) {
+        *d ^= k;
+    }
+}
+
+/// Returns bytes XORed with key, repeating key as many times as needed.
+///
+/// Panics if key is empty.
+#[cfg(feature = "alloc")]
+pub fn repeating_key_xor(bytes: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut v = bytes.to_vec();
+    repeating_key_xor_in_place(&mut v, key);
+
+    v
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed() {
+        assert_eq!(fixed_xor(&[], &[]), Ok(vec![]));
+        assert_eq!(fixed_xor(&[0xff], &[0x0f]), Ok(vec![0xf0]));
+        assert_eq!(
+            fixed_xor(&[0b1010, 0, 255], &[0b0110, 255, 255]),
+            Ok(vec![0b1100, 255, 0])
+        );
+
+        let mut dest = [1, 2, 3];
+        assert_eq!(fixed_xor_in_place(&mut dest, &[1, 2, 3]), Ok(()));
+        assert_eq!(dest, [0, 0, 0]);
+    }
+
+    #[test]
+    fn fixed_length_mismatch() {
+        assert_eq!(
+            fixed_xor(&[1, 2], &[1]),
+            Err(XorError::LengthMismatch { left: 2, right: 1 })
+        );
+
+        let mut dest = [1, 2];
+        assert_eq!(
+            fixed_xor_in_place(&mut dest, &[1, 2, 3]),
+            Err(XorError::LengthMismatch { left: 2, right: 3 })
+        );
+        // dest is unchanged on error
+        assert_eq!(dest, [1, 2]);
+
+        assert_eq!(
+            format!("{}", XorError::LengthMismatch { left: 2, right: 1 }),
+            "XOR buffers must be the same length, but they are 2 and 1 bytes long"
+        );
+    }
+
+    #[test]
+    fn single_byte() {
+        assert_eq!(single_byte_xor(&[], 0x55), []);
+        assert_eq!(
+            single_byte_xor(&[0x00, 0xff, 0x55], 0x55),
+            [0x55, 0xaa, 0x00]
+        );
+        assert_eq!(single_byte_xor(&[1, 2, 3], 0), [1, 2, 3]);
+    }
+
+    #[test]
+    fn repeating_key() {
+        assert_eq!(repeating_key_xor(&[], &[1]), []);
+        assert_eq!(
+            repeating_key_xor(&[0, 0, 0, 0, 0], &[1, 2]),
+            [1, 2, 1, 2, 1]
+        );
+        // Single-byte keys are equivalent to single_byte_xor()
+        assert_eq!(
+            repeating_key_xor(&[9, 8, 7], &[0x42]),
+            single_byte_xor(&[9, 8, 7], 0x42)
+        );
+        // Keys longer than the input are truncated
+        assert_eq!(repeating_key_xor(&[0, 0], &[1, 2, 3]), [1, 2]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let plaintext = b"YELLOW SUBMARINE";
+        let ciphertext = repeating_key_xor(plaintext, b"ICE");
+        assert_ne!(ciphertext, plaintext);
+        assert_eq!(repeating_key_xor(&ciphertext, b"ICE"), plaintext);
+    }
+
+    #[test]
+    #[should_panic(expected = "key must not be empty")]
+    fn invalid_repeating_key_empty() {
+        repeating_key_xor(&[1, 2, 3], &[]);
+    }
+}